@@ -1,152 +1,81 @@
-use std::collections::HashMap;
+use std::collections::{BTreeMap, HashMap};
 use std::fmt::{self, Display};
 use std::slice::Iter;
 
+use serde::de::Error;
+use serde::ser::SerializeMap;
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
 use crate::inputs::key::Key;
 
-#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+#[derive(Debug, Clone, Copy, Eq, PartialEq, Hash)]
 pub enum Action {
     Quit,
-    Typing(char),
     BackwardDeleteChar,
+    TogglePause,
+    Up,
+    Down,
+    Left,
+    Right,
+    Select,
+    Back,
+    Retry,
+    NextText,
+    SwitchHeatmap,
 }
 
 impl Action {
     pub fn iterator() -> Iter<'static, Action> {
-        static ACTIONS: [Action; 63] = [
+        static ACTIONS: [Action; 12] = [
             Action::Quit,
-            Action::Typing('a'),
-            Action::Typing('b'),
-            Action::Typing('c'),
-            Action::Typing('d'),
-            Action::Typing('e'),
-            Action::Typing('f'),
-            Action::Typing('g'),
-            Action::Typing('h'),
-            Action::Typing('i'),
-            Action::Typing('j'),
-            Action::Typing('k'),
-            Action::Typing('l'),
-            Action::Typing('m'),
-            Action::Typing('n'),
-            Action::Typing('o'),
-            Action::Typing('p'),
-            Action::Typing('q'),
-            Action::Typing('r'),
-            Action::Typing('s'),
-            Action::Typing('t'),
-            Action::Typing('u'),
-            Action::Typing('v'),
-            Action::Typing('w'),
-            Action::Typing('x'),
-            Action::Typing('y'),
-            Action::Typing('z'),
-            Action::Typing('A'),
-            Action::Typing('B'),
-            Action::Typing('C'),
-            Action::Typing('D'),
-            Action::Typing('E'),
-            Action::Typing('F'),
-            Action::Typing('G'),
-            Action::Typing('H'),
-            Action::Typing('I'),
-            Action::Typing('J'),
-            Action::Typing('K'),
-            Action::Typing('L'),
-            Action::Typing('M'),
-            Action::Typing('N'),
-            Action::Typing('O'),
-            Action::Typing('P'),
-            Action::Typing('Q'),
-            Action::Typing('R'),
-            Action::Typing('S'),
-            Action::Typing('T'),
-            Action::Typing('U'),
-            Action::Typing('V'),
-            Action::Typing('W'),
-            Action::Typing('X'),
-            Action::Typing('Y'),
-            Action::Typing('Z'),
-            Action::Typing(' '),
-            Action::Typing('.'),
-            Action::Typing('\''),
-            Action::Typing('"'),
-            Action::Typing(')'),
-            Action::Typing('('),
-            Action::Typing('?'),
-            Action::Typing(','),
-            Action::Typing('-'),
             Action::BackwardDeleteChar,
+            Action::TogglePause,
+            Action::Up,
+            Action::Down,
+            Action::Left,
+            Action::Right,
+            Action::Select,
+            Action::Back,
+            Action::Retry,
+            Action::NextText,
+            Action::SwitchHeatmap,
         ];
         ACTIONS.iter()
     }
 
-    pub fn keys(&self) -> &[Key] {
+    /// Name of the action in the config file.
+    pub fn name(&self) -> &'static str {
+        match self {
+            Action::Quit => "quit",
+            Action::BackwardDeleteChar => "backward_delete_char",
+            Action::TogglePause => "toggle_pause",
+            Action::Up => "up",
+            Action::Down => "down",
+            Action::Left => "left",
+            Action::Right => "right",
+            Action::Select => "select",
+            Action::Back => "back",
+            Action::Retry => "retry",
+            Action::NextText => "next_text",
+            Action::SwitchHeatmap => "switch_heatmap",
+        }
+    }
+
+    /// Default keys of the action.
+    pub fn keys(&self) -> &'static [Key] {
         match self {
             Action::Quit => &[Key::Ctrl('c')],
-            Action::Typing('a') => &[Key::Char('a')],
-            Action::Typing('b') => &[Key::Char('b')],
-            Action::Typing('c') => &[Key::Char('c')],
-            Action::Typing('d') => &[Key::Char('d')],
-            Action::Typing('e') => &[Key::Char('e')],
-            Action::Typing('f') => &[Key::Char('f')],
-            Action::Typing('g') => &[Key::Char('g')],
-            Action::Typing('h') => &[Key::Char('h')],
-            Action::Typing('i') => &[Key::Char('i')],
-            Action::Typing('j') => &[Key::Char('j')],
-            Action::Typing('k') => &[Key::Char('k')],
-            Action::Typing('l') => &[Key::Char('l')],
-            Action::Typing('m') => &[Key::Char('m')],
-            Action::Typing('n') => &[Key::Char('n')],
-            Action::Typing('o') => &[Key::Char('o')],
-            Action::Typing('p') => &[Key::Char('p')],
-            Action::Typing('q') => &[Key::Char('q')],
-            Action::Typing('r') => &[Key::Char('r')],
-            Action::Typing('s') => &[Key::Char('s')],
-            Action::Typing('t') => &[Key::Char('t')],
-            Action::Typing('u') => &[Key::Char('u')],
-            Action::Typing('v') => &[Key::Char('v')],
-            Action::Typing('w') => &[Key::Char('w')],
-            Action::Typing('x') => &[Key::Char('x')],
-            Action::Typing('y') => &[Key::Char('y')],
-            Action::Typing('z') => &[Key::Char('z')],
-            Action::Typing(' ') => &[Key::Char(' ')],
-            Action::Typing('A') => &[Key::Char('A')],
-            Action::Typing('B') => &[Key::Char('B')],
-            Action::Typing('C') => &[Key::Char('C')],
-            Action::Typing('D') => &[Key::Char('D')],
-            Action::Typing('E') => &[Key::Char('E')],
-            Action::Typing('F') => &[Key::Char('F')],
-            Action::Typing('G') => &[Key::Char('G')],
-            Action::Typing('H') => &[Key::Char('H')],
-            Action::Typing('I') => &[Key::Char('I')],
-            Action::Typing('J') => &[Key::Char('J')],
-            Action::Typing('K') => &[Key::Char('K')],
-            Action::Typing('L') => &[Key::Char('L')],
-            Action::Typing('M') => &[Key::Char('M')],
-            Action::Typing('N') => &[Key::Char('N')],
-            Action::Typing('O') => &[Key::Char('O')],
-            Action::Typing('P') => &[Key::Char('P')],
-            Action::Typing('Q') => &[Key::Char('Q')],
-            Action::Typing('R') => &[Key::Char('R')],
-            Action::Typing('S') => &[Key::Char('S')],
-            Action::Typing('T') => &[Key::Char('T')],
-            Action::Typing('U') => &[Key::Char('U')],
-            Action::Typing('V') => &[Key::Char('V')],
-            Action::Typing('W') => &[Key::Char('W')],
-            Action::Typing('X') => &[Key::Char('X')],
-            Action::Typing('Y') => &[Key::Char('Y')],
-            Action::Typing('Z') => &[Key::Char('Z')],
-            Action::Typing('.') => &[Key::Char('.')],
-            Action::Typing('\'') => &[Key::Char('\'')],
-            Action::Typing('"') => &[Key::Char('"')],
-            Action::Typing('(') => &[Key::Char('(')],
-            Action::Typing(')') => &[Key::Char(')')],
-            Action::Typing('?') => &[Key::Char('?')],
-            Action::Typing(',') => &[Key::Char(',')],
-            Action::Typing('-') => &[Key::Char('-')],
             Action::BackwardDeleteChar => &[Key::Backspace],
-            _ => panic!("should not reach"),
+            Action::TogglePause => &[Key::Ctrl('p')],
+            Action::Up => &[Key::Up],
+            Action::Down => &[Key::Down],
+            Action::Left => &[Key::Left],
+            Action::Right => &[Key::Right],
+            Action::Select => &[Key::Enter],
+            Action::Back => &[Key::Esc],
+            Action::Retry => &[Key::Ctrl('r')],
+            Action::NextText => &[Key::Ctrl('n')],
+            Action::SwitchHeatmap => &[Key::Tab],
         }
     }
 }
@@ -155,70 +84,17 @@ impl Display for Action {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         let str = match self {
             Action::Quit => "Quit",
-            Action::Typing('a') => "a",
-            Action::Typing('b') => "b",
-            Action::Typing('c') => "c",
-            Action::Typing('d') => "d",
-            Action::Typing('e') => "e",
-            Action::Typing('f') => "f",
-            Action::Typing('g') => "g",
-            Action::Typing('h') => "h",
-            Action::Typing('i') => "i",
-            Action::Typing('j') => "j",
-            Action::Typing('k') => "k",
-            Action::Typing('l') => "l",
-            Action::Typing('m') => "m",
-            Action::Typing('n') => "n",
-            Action::Typing('o') => "o",
-            Action::Typing('p') => "p",
-            Action::Typing('q') => "q",
-            Action::Typing('r') => "r",
-            Action::Typing('s') => "s",
-            Action::Typing('t') => "t",
-            Action::Typing('u') => "u",
-            Action::Typing('v') => "v",
-            Action::Typing('w') => "w",
-            Action::Typing('x') => "x",
-            Action::Typing('y') => "y",
-            Action::Typing('z') => "z",
-            Action::Typing('A') => "A",
-            Action::Typing('B') => "B",
-            Action::Typing('C') => "C",
-            Action::Typing('D') => "D",
-            Action::Typing('E') => "E",
-            Action::Typing('F') => "F",
-            Action::Typing('G') => "G",
-            Action::Typing('H') => "H",
-            Action::Typing('I') => "I",
-            Action::Typing('J') => "J",
-            Action::Typing('K') => "K",
-            Action::Typing('L') => "L",
-            Action::Typing('M') => "M",
-            Action::Typing('N') => "N",
-            Action::Typing('O') => "O",
-            Action::Typing('P') => "P",
-            Action::Typing('Q') => "Q",
-            Action::Typing('R') => "R",
-            Action::Typing('S') => "S",
-            Action::Typing('T') => "T",
-            Action::Typing('U') => "U",
-            Action::Typing('V') => "V",
-            Action::Typing('W') => "W",
-            Action::Typing('X') => "X",
-            Action::Typing('Y') => "Y",
-            Action::Typing('Z') => "Z",
-            Action::Typing(' ') => " ",
-            Action::Typing('.') => ".",
-            Action::Typing('\'') => "\'",
-            Action::Typing('"') => "\"",
-            Action::Typing('(') => "(",
-            Action::Typing(')') => ")",
-            Action::Typing('?') => "?",
-            Action::Typing(',') => ",",
-            Action::Typing('-') => "-",
-
             Action::BackwardDeleteChar => "remove char",
-            _ => panic!("should not reach"),
+            Action::TogglePause => "Pause/Resume",
+            Action::Up => "Up",
+            Action::Down => "Down",
+            Action::Left => "Left",
+            Action::Right => "Right",
+            Action::Select => "Select",
+            Action::Back => "Back",
+            Action::Retry => "Retry",
+            Action::NextText => "Next text",
+            Action::SwitchHeatmap => "Switch heatmap",
         };
         write!(f, "{}", str)
     }
@@ -228,10 +104,10 @@ impl Display for Action {
 pub struct Actions(Vec<Action>);
 
 impl Actions {
-    pub fn find(&self, key: Key) -> Option<&Action> {
+    pub fn find(&self, key: Key, bindings: &KeyBindings) -> Option<&Action> {
         Action::iterator()
             .filter(|action| self.0.contains(action))
-            .find(|action| action.keys().contains(&key))
+            .find(|action| bindings.keys(**action).contains(&key))
     }
 
     pub fn actions(&self) -> &[Action] {
@@ -272,6 +148,123 @@ impl From<Vec<Action>> for Actions {
     }
 }
 
+/// Keys bound to each action, the default ones unless the config file
+/// binds others.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct KeyBindings(HashMap<Action, Vec<Key>>);
+
+impl KeyBindings {
+    pub fn keys(&self, action: Action) -> &[Key] {
+        self.0.get(&action).map_or(action.keys(), Vec::as_slice)
+    }
+
+    /// Binds the keys instead of the current ones. Only the keys that differ
+    /// from the defaults are kept.
+    pub fn bind(&mut self, action: Action, keys: Vec<Key>) {
+        if keys == action.keys() {
+            self.0.remove(&action);
+        } else {
+            self.0.insert(action, keys);
+        }
+    }
+
+    /// The first key of the action, as shown in the help.
+    pub fn label(&self, action: Action) -> String {
+        self.keys(action)
+            .first()
+            .map(Key::to_string)
+            .unwrap_or_default()
+    }
+
+    /// Bindings that would make an action unreachable, or a character
+    /// impossible to type.
+    pub fn problems(&self) -> Vec<String> {
+        let mut problems = vec![];
+        let mut bound: HashMap<Key, Action> = HashMap::new();
+
+        for action in Action::iterator() {
+            let keys = self.keys(*action);
+            if keys.is_empty() {
+                problems.push(format!("no key bound to {}", action.name()));
+            }
+            for key in keys {
+                if matches!(key, Key::Char(_) | Key::Unknown) {
+                    problems.push(format!(
+                        "{} cannot be bound to {}, it is typed during tests",
+                        key,
+                        action.name()
+                    ));
+                }
+                if let Some(other) = bound.insert(*key, *action) {
+                    problems.push(format!(
+                        "{} is bound to both {} and {}",
+                        key,
+                        other.name(),
+                        action.name()
+                    ));
+                }
+            }
+        }
+
+        problems
+    }
+}
+
+/// Stored as a table of action names to lists of keys, such as
+/// `retry = ["<Ctrl+r>"]`.
+impl Serialize for KeyBindings {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        let mut map = serializer.serialize_map(Some(Action::iterator().len()))?;
+        for action in Action::iterator() {
+            let keys = self
+                .keys(*action)
+                .iter()
+                .map(Key::to_string)
+                .collect::<Vec<_>>();
+            map.serialize_entry(action.name(), &keys)?;
+        }
+        map.end()
+    }
+}
+
+/// A single key or a list of keys.
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum KeyList {
+    One(String),
+    Many(Vec<String>),
+}
+
+impl<'de> Deserialize<'de> for KeyBindings {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        let table = BTreeMap::<String, KeyList>::deserialize(deserializer)?;
+        let mut bindings = KeyBindings::default();
+
+        for (name, keys) in table {
+            let action = Action::iterator()
+                .find(|action| action.name() == name)
+                .ok_or_else(|| D::Error::custom(format!("unknown action '{}'", name)))?;
+            let keys = match keys {
+                KeyList::One(key) => vec![key],
+                KeyList::Many(keys) => keys,
+            };
+            let keys = keys
+                .iter()
+                .map(|key| key.parse().map_err(D::Error::custom))
+                .collect::<Result<Vec<Key>, _>>()?;
+            bindings.bind(*action, keys);
+        }
+
+        Ok(bindings)
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -279,14 +272,14 @@ mod tests {
     #[test]
     fn should_find_action_by_key() {
         let actions: Actions = vec![Action::Quit].into();
-        let result = actions.find(Key::Ctrl('c'));
+        let result = actions.find(Key::Ctrl('c'), &KeyBindings::default());
         assert_eq!(result, Some(&Action::Quit));
     }
 
     #[test]
     fn should_find_action_by_key_not_found() {
         let actions: Actions = vec![Action::Quit].into();
-        let result = actions.find(Key::Alt('w'));
+        let result = actions.find(Key::Alt('w'), &KeyBindings::default());
         assert_eq!(result, None);
     }
 
@@ -300,4 +293,38 @@ mod tests {
     fn should_panic_when_create_actions_conflict_key() {
         let _actions: Actions = vec![Action::Quit, Action::Quit].into();
     }
+
+    #[test]
+    fn should_find_action_by_bound_key() {
+        let actions: Actions = vec![Action::Quit, Action::Retry].into();
+        let mut bindings = KeyBindings::default();
+        bindings.bind(Action::Quit, vec![Key::Ctrl('q'), Key::Esc]);
+
+        assert_eq!(actions.find(Key::Esc, &bindings), Some(&Action::Quit));
+        assert_eq!(actions.find(Key::Ctrl('c'), &bindings), None);
+        assert_eq!(
+            actions.find(Key::Ctrl('r'), &bindings),
+            Some(&Action::Retry)
+        );
+        assert_eq!(bindings.label(Action::Quit), "<Ctrl+q>");
+    }
+
+    #[test]
+    fn should_report_conflicting_bindings() {
+        assert!(KeyBindings::default().problems().is_empty());
+
+        let mut bindings = KeyBindings::default();
+        bindings.bind(Action::Retry, vec![Key::Tab]);
+        bindings.bind(Action::Quit, vec![Key::Char('q')]);
+        bindings.bind(Action::Back, vec![]);
+
+        assert_eq!(
+            bindings.problems(),
+            vec![
+                "<q> cannot be bound to quit, it is typed during tests",
+                "no key bound to back",
+                "<Tab> is bound to both retry and switch_heatmap",
+            ]
+        );
+    }
 }
@@ -0,0 +1,17 @@
+use std::time::Instant;
+
+/// Where the app reads the current time from. Tests use a clock they move
+/// by hand, so timings and scores do not depend on how fast they run.
+pub trait Clock: Send + Sync {
+    fn now(&self) -> Instant;
+}
+
+/// The monotonic clock of the system.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct SystemClock;
+
+impl Clock for SystemClock {
+    fn now(&self) -> Instant {
+        Instant::now()
+    }
+}
@@ -0,0 +1,210 @@
+use std::path::PathBuf;
+use std::time::Duration;
+
+use eyre::{eyre, Result};
+use serde::{Deserialize, Serialize};
+
+use crate::app::actions::KeyBindings;
+use crate::app::ghost::GhostRun;
+use crate::app::heatmap::KeyboardLayout;
+use crate::engine::mode::TestMode;
+use crate::io::library::DEFAULT_TEXTS_DIR;
+
+/// Tick rates accepted in the config file, in milliseconds.
+pub const TICK_RATE_RANGE: (u64, u64) = (10, 1000);
+
+/// Defaults read from the config file. Every setting is optional, and the
+/// ones left out keep their default value.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct Config {
+    /// Interval between two redraws when no key is pressed.
+    pub tick_rate_ms: u64,
+    /// Mode of the menu, and of the tests started from it.
+    pub mode: TestMode,
+    pub theme: String,
+    pub texts_dir: PathBuf,
+    pub keyboard_layout: KeyboardLayout,
+    /// Earlier run of the passage raced during tests.
+    pub ghost: GhostRun,
+    pub keys: KeyBindings,
+}
+
+impl Default for Config {
+    fn default() -> Self {
+        Self {
+            tick_rate_ms: 100,
+            mode: TestMode::default(),
+            theme: "default".to_owned(),
+            texts_dir: PathBuf::from(DEFAULT_TEXTS_DIR),
+            keyboard_layout: KeyboardLayout::default(),
+            ghost: GhostRun::default(),
+            keys: KeyBindings::default(),
+        }
+    }
+}
+
+/// Settings given on the command line, which take precedence over the
+/// config file.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Overrides {
+    pub mode: Option<TestMode>,
+    /// Id of the text to type.
+    pub text: Option<String>,
+    pub theme: Option<String>,
+    pub texts_dir: Option<PathBuf>,
+    pub ghost: Option<GhostRun>,
+}
+
+impl Overrides {
+    /// A test is started right away, without going through the menu.
+    pub fn skips_menu(&self) -> bool {
+        self.mode.is_some() || self.text.is_some()
+    }
+}
+
+impl Config {
+    pub fn from_toml(content: &str) -> Result<Self> {
+        let config: Config = toml::from_str(content).map_err(|err| eyre!("{}", err))?;
+        config.validate()?;
+        Ok(config)
+    }
+
+    pub fn to_toml(&self) -> Result<String> {
+        Ok(toml::to_string(self)?)
+    }
+
+    /// Checks the settings that parse but cannot be used, listing every
+    /// problem at once.
+    pub fn validate(&self) -> Result<()> {
+        let mut problems = vec![];
+
+        let (min, max) = TICK_RATE_RANGE;
+        if !(min..=max).contains(&self.tick_rate_ms) {
+            problems.push(format!(
+                "tick_rate_ms must be between {} and {}, got {}",
+                min, max, self.tick_rate_ms
+            ));
+        }
+        if self.theme.trim().is_empty() {
+            problems.push("theme cannot be empty".to_owned());
+        }
+        problems.extend(
+            self.keys
+                .problems()
+                .into_iter()
+                .map(|problem| format!("keys: {}", problem)),
+        );
+
+        if problems.is_empty() {
+            Ok(())
+        } else {
+            Err(eyre!("{}", problems.join("\n")))
+        }
+    }
+
+    pub fn tick_rate(&self) -> Duration {
+        Duration::from_millis(self.tick_rate_ms)
+    }
+
+    /// The config with the command line settings applied.
+    pub fn merged(&self, overrides: &Overrides) -> Self {
+        let mut config = self.clone();
+        if let Some(mode) = overrides.mode {
+            config.mode = mode;
+        }
+        if let Some(theme) = &overrides.theme {
+            config.theme = theme.clone();
+        }
+        if let Some(texts_dir) = &overrides.texts_dir {
+            config.texts_dir = texts_dir.clone();
+        }
+        if let Some(ghost) = overrides.ghost {
+            config.ghost = ghost;
+        }
+        config
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::app::actions::Action;
+    use crate::inputs::key::Key;
+
+    #[test]
+    fn should_default_missing_settings() {
+        let config =
+            Config::from_toml("mode = \"50w\"\nkeyboard_layout = \"dvorak\"\nghost = \"best\"")
+                .unwrap();
+
+        assert_eq!(config.mode, TestMode::Words(50));
+        assert_eq!(config.keyboard_layout, KeyboardLayout::Dvorak);
+        assert_eq!(config.ghost, GhostRun::Best);
+        assert_eq!(config.tick_rate_ms, 100);
+        assert_eq!(Config::from_toml("").unwrap(), Config::default());
+    }
+
+    #[test]
+    fn should_read_key_bindings() {
+        let config = Config::from_toml(
+            r#"
+[keys]
+quit = "ctrl+q"
+retry = ["<Ctrl+r>", "alt+r"]
+"#,
+        )
+        .unwrap();
+
+        assert_eq!(config.keys.keys(Action::Quit), &[Key::Ctrl('q')]);
+        assert_eq!(
+            config.keys.keys(Action::Retry),
+            &[Key::Ctrl('r'), Key::Alt('r')]
+        );
+        assert_eq!(config.keys.keys(Action::Back), &[Key::Esc]);
+    }
+
+    #[test]
+    fn should_reject_invalid_config() {
+        let error = |content| Config::from_toml(content).unwrap_err().to_string();
+
+        assert!(error("mode = \"forever\"").contains("invalid mode 'forever'"));
+        assert!(error("colour = \"red\"").contains("unknown field `colour`"));
+        assert!(error("[keys]\njump = \"tab\"").contains("unknown action 'jump'"));
+        assert_eq!(
+            error("tick_rate_ms = 5\n[keys]\nquit = \"q\""),
+            "tick_rate_ms must be between 10 and 1000, got 5\n\
+             keys: <q> cannot be bound to quit, it is typed during tests"
+        );
+    }
+
+    #[test]
+    fn should_save_and_read_back() {
+        let mut config = Config {
+            mode: TestMode::Passage,
+            theme: "light".to_owned(),
+            ..Config::default()
+        };
+        config.keys.bind(Action::NextText, vec![Key::Ctrl('t')]);
+
+        assert_eq!(
+            Config::from_toml(&config.to_toml().unwrap()).unwrap(),
+            config
+        );
+    }
+
+    #[test]
+    fn should_prefer_command_line() {
+        let config = Config {
+            theme: "light".to_owned(),
+            ..Config::default()
+        };
+        let merged = config.merged(&Overrides {
+            mode: Some(TestMode::Words(10)),
+            ..Overrides::default()
+        });
+
+        assert_eq!(merged.mode, TestMode::Words(10));
+        assert_eq!(merged.theme, "light");
+    }
+}
@@ -0,0 +1,36 @@
+//! Moving through the items and values of the menu, settings and history
+//! screens, wrapping around at both ends.
+
+pub fn previous(index: usize, len: usize) -> usize {
+    (index + len - 1) % len
+}
+
+pub fn next(index: usize, len: usize) -> usize {
+    (index + 1) % len
+}
+
+/// The value before or after `current`, or the first one when `current` is
+/// not offered.
+pub fn cycle<T: Clone + PartialEq>(
+    values: &[T],
+    current: &T,
+    step: fn(usize, usize) -> usize,
+) -> T {
+    match values.iter().position(|value| value == current) {
+        Some(index) => values[step(index, values.len())].clone(),
+        None => values[0].clone(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn should_wrap_around() {
+        assert_eq!(previous(0, 3), 2);
+        assert_eq!(next(2, 3), 0);
+        assert_eq!(cycle(&[1, 2, 3], &3, next), 1);
+        assert_eq!(cycle(&[1, 2, 3], &7, previous), 1);
+    }
+}
@@ -0,0 +1,163 @@
+use std::fmt::{self, Display};
+use std::str::FromStr;
+use std::time::Duration;
+
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+use crate::app::replay::Replay;
+use crate::engine::Input;
+
+/// The earlier run of a passage that the ghost types again.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum GhostRun {
+    #[default]
+    Off,
+    /// The fastest run.
+    Best,
+    /// The most recent run.
+    Last,
+}
+
+impl GhostRun {
+    pub const ALL: [GhostRun; 3] = [GhostRun::Off, GhostRun::Best, GhostRun::Last];
+
+    /// The run to race among the replays of a passage, oldest first.
+    pub fn pick<'a>(&self, replays: &'a [Replay]) -> Option<&'a Replay> {
+        match self {
+            GhostRun::Off => None,
+            GhostRun::Best => replays.iter().max_by(|a, b| a.wpm.total_cmp(&b.wpm)),
+            GhostRun::Last => replays.last(),
+        }
+    }
+}
+
+impl Display for GhostRun {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            GhostRun::Off => write!(f, "off"),
+            GhostRun::Best => write!(f, "best"),
+            GhostRun::Last => write!(f, "last"),
+        }
+    }
+}
+
+impl FromStr for GhostRun {
+    type Err = String;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        let value = value.trim().to_lowercase();
+        GhostRun::ALL
+            .into_iter()
+            .find(|run| run.to_string() == value)
+            .ok_or_else(|| format!("unknown ghost '{}', expected off, best or last", value))
+    }
+}
+
+/// Stored by name, such as `best`.
+impl Serialize for GhostRun {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        serializer.serialize_str(&self.to_string())
+    }
+}
+
+impl<'de> Deserialize<'de> for GhostRun {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        let value = String::deserialize(deserializer)?;
+        value.parse().map_err(serde::de::Error::custom)
+    }
+}
+
+/// Where the cursor of an earlier run was at each moment, so the player can
+/// race against it. Both runs start with their first keystroke.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Ghost {
+    pub run: GhostRun,
+    pub wpm: f64,
+    /// The text of the earlier run, which the race is on.
+    pub text: String,
+    /// Cursor after each keystroke, with the offset of the keystroke.
+    positions: Vec<(Duration, usize)>,
+}
+
+impl Ghost {
+    pub fn new(run: GhostRun, replay: &Replay) -> Self {
+        let mut cursor: usize = 0;
+        let positions = replay
+            .keystrokes
+            .iter()
+            .map(|keystroke| {
+                cursor = match keystroke.input {
+                    Input::Char(_) => cursor + 1,
+                    Input::Backspace => cursor.saturating_sub(1),
+                };
+                (keystroke.at(), cursor)
+            })
+            .collect();
+
+        Self {
+            run,
+            wpm: replay.wpm,
+            text: replay.text.clone(),
+            positions,
+        }
+    }
+
+    /// Position of the cursor of the ghost, `elapsed` into the test.
+    pub fn position(&self, elapsed: Duration) -> usize {
+        let typed = self.positions.partition_point(|(at, _)| *at < elapsed);
+        typed
+            .checked_sub(1)
+            .map_or(0, |last| self.positions[last].1)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn should_pick_the_run_to_race() {
+        let replays = [
+            Replay::sample("1", 40.0, &[]),
+            Replay::sample("1", 65.0, &[]),
+            Replay::sample("1", 50.0, &[]),
+        ];
+
+        assert_eq!(GhostRun::Best.pick(&replays).unwrap().wpm, 65.0);
+        assert_eq!(GhostRun::Last.pick(&replays).unwrap().wpm, 50.0);
+        assert!(GhostRun::Off.pick(&replays).is_none());
+        assert!(GhostRun::Best.pick(&[]).is_none());
+        assert_eq!("Last".parse::<GhostRun>(), Ok(GhostRun::Last));
+        assert!("worst".parse::<GhostRun>().is_err());
+    }
+
+    #[test]
+    fn should_follow_the_earlier_cursor() {
+        let ghost = Ghost::new(
+            GhostRun::Best,
+            &Replay::sample(
+                "1",
+                30.0,
+                &[
+                    (Input::Char('a'), 0),
+                    (Input::Char('x'), 300),
+                    (Input::Backspace, 500),
+                    (Input::Char('b'), 700),
+                ],
+            ),
+        );
+
+        let position = |millis| ghost.position(Duration::from_millis(millis));
+        assert_eq!(position(0), 0);
+        assert_eq!(position(1), 1);
+        assert_eq!(position(400), 2);
+        assert_eq!(position(600), 1);
+        assert_eq!(position(5000), 2);
+    }
+}
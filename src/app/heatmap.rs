@@ -0,0 +1,403 @@
+use std::fmt::{self, Display};
+use std::str::FromStr;
+use std::time::Duration;
+
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use tui::buffer::Buffer;
+use tui::layout::Rect;
+use tui::style::{Color, Style};
+use tui::widgets::{Block, Widget};
+
+use crate::app::theme::Theme;
+use crate::engine::stats::{KeyStat, TypingStats};
+
+/// Physical arrangement of the keys drawn by the heatmap.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum KeyboardLayout {
+    #[default]
+    Qwerty,
+    Azerty,
+    Dvorak,
+    Colemak,
+}
+
+impl KeyboardLayout {
+    pub const ALL: [KeyboardLayout; 4] = [
+        KeyboardLayout::Qwerty,
+        KeyboardLayout::Azerty,
+        KeyboardLayout::Dvorak,
+        KeyboardLayout::Colemak,
+    ];
+
+    /// Unshifted characters of each row, from the number row down.
+    pub fn rows(&self) -> [&'static str; 4] {
+        match self {
+            KeyboardLayout::Qwerty => ["1234567890-=", "qwertyuiop[]", "asdfghjkl;'", "zxcvbnm,./"],
+            KeyboardLayout::Azerty => {
+                ["&é\"'(-è_çà)=", "azertyuiop^$", "qsdfghjklmù", "wxcvbn,;:!"]
+            }
+            KeyboardLayout::Dvorak => ["1234567890[]", "',.pyfgcrl/=", "aoeuidhtns-", ";qjkxbmwvz"],
+            KeyboardLayout::Colemak => {
+                ["1234567890-=", "qwfpgjluy;[]", "arstdhneio'", "zxcvbkm,./"]
+            }
+        }
+    }
+}
+
+impl Display for KeyboardLayout {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            KeyboardLayout::Qwerty => write!(f, "qwerty"),
+            KeyboardLayout::Azerty => write!(f, "azerty"),
+            KeyboardLayout::Dvorak => write!(f, "dvorak"),
+            KeyboardLayout::Colemak => write!(f, "colemak"),
+        }
+    }
+}
+
+impl FromStr for KeyboardLayout {
+    type Err = String;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        let value = value.trim().to_lowercase();
+        KeyboardLayout::ALL
+            .into_iter()
+            .find(|layout| layout.to_string() == value)
+            .ok_or_else(|| {
+                format!(
+                    "unknown keyboard layout '{}', expected qwerty, azerty, dvorak or colemak",
+                    value
+                )
+            })
+    }
+}
+
+/// Stored by name, such as `dvorak`.
+impl Serialize for KeyboardLayout {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        serializer.serialize_str(&self.to_string())
+    }
+}
+
+impl<'de> Deserialize<'de> for KeyboardLayout {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        let value = String::deserialize(deserializer)?;
+        value.parse().map_err(serde::de::Error::custom)
+    }
+}
+
+/// What the colour of each key stands for.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum HeatmapMetric {
+    #[default]
+    ErrorRate,
+    Latency,
+}
+
+/// Which keystrokes the heatmap is drawn from.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum HeatmapSource {
+    #[default]
+    Session,
+    History,
+}
+
+/// The metric and source shown on the results screen, switched in turn.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct HeatmapSettings {
+    pub metric: HeatmapMetric,
+    pub source: HeatmapSource,
+}
+
+impl HeatmapSettings {
+    pub fn next(&mut self) {
+        *self = match (self.metric, self.source) {
+            (HeatmapMetric::ErrorRate, source) => Self {
+                metric: HeatmapMetric::Latency,
+                source,
+            },
+            (HeatmapMetric::Latency, HeatmapSource::Session) => Self {
+                metric: HeatmapMetric::ErrorRate,
+                source: HeatmapSource::History,
+            },
+            (HeatmapMetric::Latency, HeatmapSource::History) => Self::default(),
+        };
+    }
+
+    pub fn title(&self) -> &'static str {
+        match (self.metric, self.source) {
+            (HeatmapMetric::ErrorRate, HeatmapSource::Session) => "Error rate (this test)",
+            (HeatmapMetric::Latency, HeatmapSource::Session) => "Latency (this test)",
+            (HeatmapMetric::ErrorRate, HeatmapSource::History) => "Error rate (all tests)",
+            (HeatmapMetric::Latency, HeatmapSource::History) => "Latency (all tests)",
+        }
+    }
+}
+
+/// A keyboard where each key is coloured by its error rate or mean latency.
+/// Keys grow with the area, and rows that do not fit are left out.
+pub struct Heatmap<'a> {
+    stats: &'a TypingStats,
+    layout: KeyboardLayout,
+    metric: HeatmapMetric,
+    scale: [Color; 4],
+    no_data: Color,
+    key_text: Color,
+    block: Option<Block<'a>>,
+}
+
+impl<'a> Heatmap<'a> {
+    pub fn new(stats: &'a TypingStats) -> Self {
+        let theme = Theme::default();
+        Self {
+            stats,
+            layout: KeyboardLayout::default(),
+            metric: HeatmapMetric::default(),
+            scale: theme.scale,
+            no_data: theme.no_data,
+            key_text: theme.key_text,
+            block: None,
+        }
+    }
+
+    pub fn layout(mut self, layout: KeyboardLayout) -> Self {
+        self.layout = layout;
+        self
+    }
+
+    pub fn metric(mut self, metric: HeatmapMetric) -> Self {
+        self.metric = metric;
+        self
+    }
+
+    pub fn theme(mut self, theme: &Theme) -> Self {
+        self.scale = theme.scale;
+        self.no_data = theme.no_data;
+        self.key_text = theme.key_text;
+        self
+    }
+
+    pub fn block(mut self, block: Block<'a>) -> Self {
+        self.block = Some(block);
+        self
+    }
+
+    /// Statistics of a key, shifted and unshifted characters together.
+    fn key_stat(&self, key: char) -> Option<KeyStat> {
+        let mut found = None;
+        for c in std::iter::once(key).chain(key.to_uppercase().filter(|c| *c != key)) {
+            if let Some(stat) = self.stats.key(c) {
+                found.get_or_insert_with(KeyStat::default).merge(stat);
+            }
+        }
+        found
+    }
+
+    fn key_style(&self, stat: Option<KeyStat>) -> Style {
+        let level = stat.and_then(|stat| match self.metric {
+            HeatmapMetric::ErrorRate => Some(error_level(stat.error_rate())),
+            HeatmapMetric::Latency => stat.mean_latency().map(latency_level),
+        });
+        let colour = level.map_or(self.no_data, |level| self.scale[level]);
+        Style::default().fg(self.key_text).bg(colour)
+    }
+}
+
+/// Index in the colour scale, from the best keys to the worst ones.
+fn error_level(rate: f64) -> usize {
+    match rate {
+        rate if rate <= 0.02 => 0,
+        rate if rate <= 0.05 => 1,
+        rate if rate <= 0.1 => 2,
+        _ => 3,
+    }
+}
+
+fn latency_level(latency: Duration) -> usize {
+    match latency.as_millis() {
+        0..=150 => 0,
+        151..=250 => 1,
+        251..=400 => 2,
+        _ => 3,
+    }
+}
+
+impl<'a> Widget for Heatmap<'a> {
+    fn render(mut self, area: Rect, buf: &mut Buffer) {
+        let area = match self.block.take() {
+            Some(block) => {
+                let inner = block.inner(area);
+                block.render(area, buf);
+                inner
+            }
+            None => area,
+        };
+
+        let rows = self.layout.rows();
+        let widest = rows
+            .iter()
+            .map(|row| row.chars().count())
+            .max()
+            .unwrap_or(1) as u16;
+        // Each row is shifted by a fraction of a key, like on a real keyboard,
+        // and the space bar takes one more row below.
+        let key_width = (area.width / (widest + 2)).clamp(1, 7);
+        let key_height = (area.height / (rows.len() as u16 + 1)).clamp(1, 3);
+        let offsets = [0, key_width / 2, key_width * 3 / 4, key_width * 5 / 4];
+        let keyboard_width = widest * key_width + offsets[3];
+        let left = area.x + area.width.saturating_sub(keyboard_width) / 2;
+
+        for (index, row) in rows.iter().enumerate() {
+            let y = area.y + index as u16 * key_height;
+            if y + key_height > area.bottom() {
+                return;
+            }
+            for (column, key) in row.chars().enumerate() {
+                let x = left + offsets[index] + column as u16 * key_width;
+                if x + key_width > area.right() {
+                    break;
+                }
+                // Keep a gap between keys when they are wide enough.
+                let width = if key_width > 2 {
+                    key_width - 1
+                } else {
+                    key_width
+                };
+                let cell = Rect::new(x, y, width, key_height);
+                buf.set_style(cell, self.key_style(self.key_stat(key)));
+                buf.set_string(
+                    x + (width - 1) / 2,
+                    y + (key_height - 1) / 2,
+                    key.to_string(),
+                    self.key_style(self.key_stat(key)),
+                );
+            }
+        }
+
+        let y = area.y + rows.len() as u16 * key_height;
+        if y + key_height <= area.bottom() {
+            let width = (key_width * 6).min(area.width);
+            let x = area.x + area.width.saturating_sub(width) / 2;
+            let style = self.key_style(self.key_stat(' '));
+            buf.set_style(Rect::new(x, y, width, key_height), style);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::engine::score::Keystroke;
+
+    fn stats() -> TypingStats {
+        TypingStats::compute(
+            "aA",
+            &[
+                Keystroke::Typed {
+                    position: 0,
+                    expected: Some('a'),
+                    typed: 'a',
+                    at: Duration::ZERO,
+                },
+                Keystroke::Typed {
+                    position: 1,
+                    expected: Some('A'),
+                    typed: 'x',
+                    at: Duration::from_millis(500),
+                },
+            ],
+        )
+    }
+
+    #[test]
+    fn should_parse_layouts() {
+        assert_eq!("Dvorak".parse(), Ok(KeyboardLayout::Dvorak));
+        assert!("bepo".parse::<KeyboardLayout>().is_err());
+        for layout in KeyboardLayout::ALL {
+            assert_eq!(layout.to_string().parse(), Ok(layout));
+        }
+    }
+
+    #[test]
+    fn should_cycle_through_settings() {
+        let mut settings = HeatmapSettings::default();
+        settings.next();
+        assert_eq!(settings.metric, HeatmapMetric::Latency);
+        settings.next();
+        assert_eq!(settings.source, HeatmapSource::History);
+        settings.next();
+        settings.next();
+        assert_eq!(settings, HeatmapSettings::default());
+    }
+
+    #[test]
+    fn should_merge_shifted_characters() {
+        let stats = stats();
+        let stat = Heatmap::new(&stats).key_stat('a').unwrap();
+
+        assert_eq!(stat.count, 2);
+        assert_eq!(stat.errors, 1);
+    }
+
+    #[test]
+    fn should_colour_keys_by_metric() {
+        let area = Rect::new(0, 0, 50, 10);
+        let stats = stats();
+
+        let mut buf = Buffer::empty(area);
+        Heatmap::new(&stats).render(area, &mut buf);
+        let (x, y) = (0..area.width)
+            .flat_map(|x| (0..area.height).map(move |y| (x, y)))
+            .find(|(x, y)| buf.get(*x, *y).symbol == "a")
+            .unwrap();
+        assert_eq!(buf.get(x, y).bg, Color::Red);
+        assert_eq!(buf.get(x + 1, y).bg, Color::Red);
+
+        let mut buf = Buffer::empty(area);
+        Heatmap::new(&stats)
+            .metric(HeatmapMetric::Latency)
+            .render(area, &mut buf);
+        assert_eq!(buf.get(x, y).bg, Color::Red);
+        let q = (0..area.width)
+            .flat_map(|x| (0..area.height).map(move |y| (x, y)))
+            .find(|(x, y)| buf.get(*x, *y).symbol == "q")
+            .unwrap();
+        assert_eq!(buf.get(q.0, q.1).bg, Color::DarkGray);
+    }
+
+    #[test]
+    fn should_use_theme_colours() {
+        let area = Rect::new(0, 0, 50, 10);
+        let stats = stats();
+        let theme = Theme::colour_blind();
+
+        let mut buf = Buffer::empty(area);
+        Heatmap::new(&stats).theme(&theme).render(area, &mut buf);
+        let cells = |symbol: &str| {
+            (0..area.width)
+                .flat_map(|x| (0..area.height).map(move |y| (x, y)))
+                .find(|(x, y)| buf.get(*x, *y).symbol == symbol)
+                .map(|(x, y)| buf.get(x, y).clone())
+                .unwrap()
+        };
+        assert_eq!(cells("a").bg, theme.scale[3]);
+        assert_eq!(cells("q").bg, theme.no_data);
+        assert_eq!(cells("q").fg, theme.key_text);
+    }
+
+    #[test]
+    fn should_fit_in_small_areas() {
+        let stats = stats();
+        for (width, height) in [(1, 1), (10, 2), (50, 4), (200, 60)] {
+            let area = Rect::new(0, 0, width, height);
+            let mut buf = Buffer::empty(area);
+            Heatmap::new(&stats).render(area, &mut buf);
+        }
+    }
+}
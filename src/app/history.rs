@@ -0,0 +1,172 @@
+use crate::app::cycle::{next, previous};
+use crate::engine::mode::TestMode;
+use crate::io::history::{History, HistoryQuery, SessionRecord};
+
+/// Past sessions shown on the history screen, filtered by mode with left and
+/// right, and by passage with up and down.
+#[derive(Debug, Clone, Default)]
+pub struct HistoryView {
+    history: History,
+    modes: Vec<TestMode>,
+    passages: Vec<(String, String)>,
+    /// Index in `modes`, shifted by one: 0 stands for every mode.
+    mode: usize,
+    /// Index in `passages`, shifted by one: 0 stands for every passage.
+    passage: usize,
+}
+
+impl HistoryView {
+    pub fn new(history: History) -> Self {
+        let mut modes: Vec<TestMode> = vec![];
+        let mut passages: Vec<(String, String)> = vec![];
+        for record in history.records() {
+            if !modes.contains(&record.mode) {
+                modes.push(record.mode);
+            }
+            if !passages.iter().any(|(id, _)| *id == record.passage_id) {
+                passages.push((record.passage_id.clone(), record.from.clone()));
+            }
+        }
+        modes.sort_by_key(mode_order);
+        passages.sort();
+
+        Self {
+            history,
+            modes,
+            passages,
+            mode: 0,
+            passage: 0,
+        }
+    }
+
+    pub fn previous_mode(&mut self) {
+        self.mode = previous(self.mode, self.modes.len() + 1);
+    }
+
+    pub fn next_mode(&mut self) {
+        self.mode = next(self.mode, self.modes.len() + 1);
+    }
+
+    pub fn previous_passage(&mut self) {
+        self.passage = previous(self.passage, self.passages.len() + 1);
+    }
+
+    pub fn next_passage(&mut self) {
+        self.passage = next(self.passage, self.passages.len() + 1);
+    }
+
+    /// The chosen mode, or `None` for every mode.
+    pub fn mode(&self) -> Option<TestMode> {
+        self.mode
+            .checked_sub(1)
+            .and_then(|index| self.modes.get(index))
+            .copied()
+    }
+
+    /// The chosen passage, or `None` for every passage.
+    pub fn passage(&self) -> Option<&(String, String)> {
+        self.passage
+            .checked_sub(1)
+            .and_then(|index| self.passages.get(index))
+    }
+
+    pub fn mode_label(&self) -> String {
+        self.mode()
+            .map_or_else(|| "All".to_owned(), |mode| mode.to_string())
+    }
+
+    pub fn passage_label(&self) -> String {
+        self.passage().map_or_else(
+            || "All".to_owned(),
+            |(id, from)| format!("{} - {}", id, from),
+        )
+    }
+
+    /// Sessions matching the current filters.
+    pub fn query(&self) -> HistoryQuery<'_> {
+        let query = self.history.query();
+        let query = match self.mode() {
+            Some(mode) => query.mode(mode),
+            None => query,
+        };
+        match self.passage() {
+            Some((id, _)) => query.passage(id.as_str()),
+            None => query,
+        }
+    }
+
+    /// The best session of each mode, within the chosen passage.
+    pub fn personal_bests(&self) -> Vec<&SessionRecord> {
+        let modes = match self.mode() {
+            Some(mode) => vec![mode],
+            None => self.modes.clone(),
+        };
+        modes
+            .into_iter()
+            .filter_map(|mode| {
+                let query = self.history.query().mode(mode);
+                match self.passage() {
+                    Some((id, _)) => query.passage(id.as_str()).best(),
+                    None => query.best(),
+                }
+            })
+            .collect()
+    }
+}
+
+/// Timed tests first, then word-count tests, then full passages.
+fn mode_order(mode: &TestMode) -> (u8, u64) {
+    match mode {
+        TestMode::Timed(limit) => (0, limit.as_secs()),
+        TestMode::Words(goal) => (1, *goal as u64),
+        TestMode::Passage => (2, 0),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use super::*;
+
+    fn view() -> HistoryView {
+        let timed = TestMode::Timed(Duration::from_secs(30));
+        HistoryView::new(History::from(vec![
+            SessionRecord::sample("2", timed, 40.0),
+            SessionRecord::sample("1", timed, 60.0),
+            SessionRecord::sample("1", TestMode::Passage, 80.0),
+            SessionRecord::sample("2", TestMode::Passage, 30.0),
+        ]))
+    }
+
+    #[test]
+    fn should_show_everything_by_default() {
+        let view = view();
+        assert_eq!(view.mode_label(), "All");
+        assert_eq!(view.passage_label(), "All");
+        assert_eq!(view.query().records().len(), 4);
+
+        let bests: Vec<f64> = view.personal_bests().iter().map(|r| r.wpm).collect();
+        assert_eq!(bests, vec![60.0, 80.0]);
+    }
+
+    #[test]
+    fn should_filter_by_mode_and_passage() {
+        let mut view = view();
+
+        view.next_mode();
+        assert_eq!(view.mode_label(), "30s");
+        assert_eq!(view.query().records().len(), 2);
+
+        view.next_passage();
+        view.next_passage();
+        assert_eq!(view.passage_label(), "2 - Author 2");
+        assert_eq!(view.query().records().len(), 1);
+        let bests: Vec<f64> = view.personal_bests().iter().map(|r| r.wpm).collect();
+        assert_eq!(bests, vec![40.0]);
+
+        view.previous_mode();
+        assert_eq!(view.mode(), None);
+        assert_eq!(view.query().records().len(), 2);
+    }
+}
@@ -0,0 +1,294 @@
+use std::time::Duration;
+
+use crate::app::cycle::{next, previous};
+use crate::engine::mode::{TestMode, DURATIONS, WORD_GOALS};
+use crate::io::file::TypingFileDisplay;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MenuItem {
+    Mode,
+    Length,
+    Text,
+    Theme,
+    Start,
+    History,
+    Settings,
+    Quit,
+}
+
+impl MenuItem {
+    pub const ALL: [MenuItem; 8] = [
+        MenuItem::Mode,
+        MenuItem::Length,
+        MenuItem::Text,
+        MenuItem::Theme,
+        MenuItem::Start,
+        MenuItem::History,
+        MenuItem::Settings,
+        MenuItem::Quit,
+    ];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            MenuItem::Mode => "Mode",
+            MenuItem::Length => "Length",
+            MenuItem::Text => "Text",
+            MenuItem::Theme => "Theme",
+            MenuItem::Start => "Start",
+            MenuItem::History => "History",
+            MenuItem::Settings => "Settings",
+            MenuItem::Quit => "Quit",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum ModeKind {
+    Timed,
+    Words,
+    Passage,
+}
+
+const MODE_KINDS: [ModeKind; 3] = [ModeKind::Timed, ModeKind::Words, ModeKind::Passage];
+
+/// Selection of the main menu. Items are chosen with up and down, and their
+/// value is changed with left and right.
+#[derive(Debug, Clone)]
+pub struct Menu {
+    selected: usize,
+    mode_kind: usize,
+    duration: usize,
+    word_goal: usize,
+    /// Index in `texts`, shifted by one: 0 stands for a random text.
+    text: usize,
+    texts: Vec<(String, String)>,
+    /// Index in `themes`.
+    theme: usize,
+    themes: Vec<String>,
+}
+
+impl Menu {
+    pub fn new(mode: TestMode) -> Self {
+        let mut menu = Self {
+            selected: 0,
+            mode_kind: 0,
+            duration: DURATIONS.iter().position(|d| *d == 60).unwrap_or(0),
+            word_goal: 0,
+            text: 0,
+            texts: vec![],
+            theme: 0,
+            themes: vec![],
+        };
+        menu.set_mode(mode);
+        menu
+    }
+
+    pub fn set_mode(&mut self, mode: TestMode) {
+        match mode {
+            TestMode::Timed(limit) => {
+                self.mode_kind = 0;
+                if let Some(index) = DURATIONS.iter().position(|d| *d == limit.as_secs()) {
+                    self.duration = index;
+                }
+            }
+            TestMode::Words(goal) => {
+                self.mode_kind = 1;
+                if let Some(index) = WORD_GOALS.iter().position(|g| *g == goal) {
+                    self.word_goal = index;
+                }
+            }
+            TestMode::Passage => self.mode_kind = 2,
+        }
+    }
+
+    pub fn set_texts(&mut self, texts: &[TypingFileDisplay]) {
+        let selected = self.text_id().map(str::to_owned);
+        self.texts = texts
+            .iter()
+            .map(|text| (text.id.clone(), text.from.clone()))
+            .collect();
+        self.text = selected
+            .and_then(|id| self.texts.iter().position(|(known, _)| *known == id))
+            .map_or(0, |index| index + 1);
+    }
+
+    pub fn set_themes(&mut self, names: Vec<String>) {
+        let selected = self.theme_name().map(str::to_owned);
+        self.themes = names;
+        self.theme = selected
+            .and_then(|name| self.themes.iter().position(|known| *known == name))
+            .unwrap_or(0);
+    }
+
+    /// Selects the theme with the given name, if there is one.
+    pub fn select_theme(&mut self, name: &str) -> bool {
+        match self.themes.iter().position(|known| known == name) {
+            Some(index) => {
+                self.theme = index;
+                true
+            }
+            None => false,
+        }
+    }
+
+    pub fn selected(&self) -> MenuItem {
+        MenuItem::ALL[self.selected]
+    }
+
+    pub fn up(&mut self) {
+        self.selected = previous(self.selected, MenuItem::ALL.len());
+    }
+
+    pub fn down(&mut self) {
+        self.selected = next(self.selected, MenuItem::ALL.len());
+    }
+
+    pub fn left(&mut self) {
+        self.change(previous);
+    }
+
+    pub fn right(&mut self) {
+        self.change(next);
+    }
+
+    fn change(&mut self, step: fn(usize, usize) -> usize) {
+        match self.selected() {
+            MenuItem::Mode => self.mode_kind = step(self.mode_kind, MODE_KINDS.len()),
+            MenuItem::Length => match MODE_KINDS[self.mode_kind] {
+                ModeKind::Timed => self.duration = step(self.duration, DURATIONS.len()),
+                ModeKind::Words => self.word_goal = step(self.word_goal, WORD_GOALS.len()),
+                ModeKind::Passage => {}
+            },
+            MenuItem::Text => self.text = step(self.text, self.texts.len() + 1),
+            MenuItem::Theme if !self.themes.is_empty() => {
+                self.theme = step(self.theme, self.themes.len())
+            }
+            MenuItem::Theme => {}
+            MenuItem::Start | MenuItem::History | MenuItem::Settings | MenuItem::Quit => {}
+        }
+    }
+
+    pub fn mode(&self) -> TestMode {
+        match MODE_KINDS[self.mode_kind] {
+            ModeKind::Timed => TestMode::Timed(Duration::from_secs(DURATIONS[self.duration])),
+            ModeKind::Words => TestMode::Words(WORD_GOALS[self.word_goal]),
+            ModeKind::Passage => TestMode::Passage,
+        }
+    }
+
+    /// The chosen text, or `None` for a random one.
+    pub fn text_id(&self) -> Option<&str> {
+        self.text
+            .checked_sub(1)
+            .and_then(|index| self.texts.get(index))
+            .map(|(id, _)| id.as_str())
+    }
+
+    /// Index of the chosen theme.
+    pub fn theme(&self) -> usize {
+        self.theme
+    }
+
+    pub fn theme_name(&self) -> Option<&str> {
+        self.themes.get(self.theme).map(String::as_str)
+    }
+
+    pub fn value(&self, item: MenuItem) -> String {
+        match item {
+            MenuItem::Mode => match MODE_KINDS[self.mode_kind] {
+                ModeKind::Timed => "Timed".to_owned(),
+                ModeKind::Words => "Words".to_owned(),
+                ModeKind::Passage => "Full passage".to_owned(),
+            },
+            MenuItem::Length => self.mode().label(),
+            MenuItem::Text => match self.text.checked_sub(1).and_then(|i| self.texts.get(i)) {
+                Some((id, from)) => format!("{} - {}", id, from),
+                None => "Random".to_owned(),
+            },
+            MenuItem::Theme => self.theme_name().unwrap_or("default").to_owned(),
+            MenuItem::Start | MenuItem::History | MenuItem::Settings | MenuItem::Quit => {
+                String::new()
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn should_start_from_given_mode() {
+        let menu = Menu::new(TestMode::Words(50));
+        assert_eq!(menu.mode(), TestMode::Words(50));
+    }
+
+    #[test]
+    fn should_cycle_through_items() {
+        let mut menu = Menu::new(TestMode::default());
+        assert_eq!(menu.selected(), MenuItem::Mode);
+
+        menu.up();
+        assert_eq!(menu.selected(), MenuItem::Quit);
+
+        menu.down();
+        menu.down();
+        assert_eq!(menu.selected(), MenuItem::Length);
+    }
+
+    #[test]
+    fn should_change_mode_and_length() {
+        let mut menu = Menu::new(TestMode::Timed(Duration::from_secs(60)));
+
+        menu.down();
+        menu.right();
+        assert_eq!(menu.mode(), TestMode::Timed(Duration::from_secs(120)));
+
+        menu.up();
+        menu.left();
+        assert_eq!(menu.mode(), TestMode::Passage);
+    }
+
+    #[test]
+    fn should_pick_text() {
+        let text = TypingFileDisplay {
+            id: "1".to_owned(),
+            from: "Someone".to_owned(),
+            content: "hello".to_owned(),
+            url: "".to_owned(),
+            words_count: 1,
+        };
+        let mut menu = Menu::new(TestMode::default());
+        menu.set_texts(&[text]);
+        assert_eq!(menu.text_id(), None);
+
+        menu.down();
+        menu.down();
+        menu.right();
+        assert_eq!(menu.text_id(), Some("1"));
+        assert_eq!(menu.value(MenuItem::Text), "1 - Someone");
+    }
+
+    #[test]
+    fn should_pick_theme() {
+        let mut menu = Menu::new(TestMode::default());
+        menu.set_themes(vec!["default".to_owned(), "light".to_owned()]);
+        assert_eq!(menu.theme_name(), Some("default"));
+
+        for _ in 0..3 {
+            menu.down();
+        }
+        menu.left();
+        assert_eq!(menu.value(MenuItem::Theme), "light");
+
+        menu.set_themes(vec![
+            "amber".to_owned(),
+            "default".to_owned(),
+            "light".to_owned(),
+        ]);
+        assert_eq!(menu.theme(), 2);
+        assert!(menu.select_theme("amber"));
+        assert!(!menu.select_theme("neon"));
+        assert_eq!(menu.theme_name(), Some("amber"));
+    }
+}
@@ -1,14 +1,40 @@
-use log::{debug, error, warn};
+use std::sync::Arc;
+use std::time::Instant;
 
-use self::actions::Actions;
+use log::{debug, error, info, warn};
+
+use self::actions::{Actions, KeyBindings};
+use self::clock::{Clock, SystemClock};
+use self::config::{Config, Overrides};
+use self::ghost::Ghost;
+use self::heatmap::{HeatmapSettings, KeyboardLayout};
+use self::history::HistoryView;
+use self::menu::{Menu, MenuItem};
+use self::replay::Replay;
+use self::settings::{SettingsItem, SettingsView};
 use self::state::AppState;
+use self::theme::Theme;
 use crate::app::actions::Action;
+use crate::engine::mode::TestMode;
+use crate::engine::stats::TypingStats;
+use crate::engine::{EngineEvent, Input};
 use crate::inputs::key::Key;
-use crate::io::file::TypingFileDisplay;
+use crate::io::file::{TextError, TypingFileDisplay};
+use crate::io::history::History;
 use crate::io::IoEvent;
 
 pub mod actions;
+pub mod clock;
+pub mod config;
+mod cycle;
+pub mod ghost;
+pub mod heatmap;
+pub mod history;
+pub mod menu;
+pub mod replay;
+pub mod settings;
 pub mod state;
+pub mod theme;
 pub mod ui;
 
 #[derive(Debug, PartialEq, Eq)]
@@ -21,60 +47,262 @@ pub struct App {
     io_tx: tokio::sync::mpsc::Sender<IoEvent>,
     actions: Actions,
     is_loading: bool,
+    mode: TestMode,
+    text_id: Option<String>,
+    skip_menu: bool,
+    menu: Menu,
+    heatmap: HeatmapSettings,
+    /// The config file, as edited on the settings screen.
+    config: Config,
+    /// The config file with the command line settings applied.
+    settings: Config,
+    overall_stats: TypingStats,
+    themes: Vec<Theme>,
+    clock: Arc<dyn Clock>,
     state: AppState,
 }
 
 impl App {
-    pub fn new(io_tx: tokio::sync::mpsc::Sender<IoEvent>) -> Self {
+    /// Starts on the menu, unless a mode is given.
+    pub fn new(io_tx: tokio::sync::mpsc::Sender<IoEvent>, mode: Option<TestMode>) -> Self {
+        let overrides = Overrides {
+            mode,
+            ..Overrides::default()
+        };
+        Self::with_config(io_tx, Config::default(), &overrides)
+    }
+
+    /// Starts with the defaults of the config file, unless the command line
+    /// gives others. A mode or a text on the command line skips the menu.
+    pub fn with_config(
+        io_tx: tokio::sync::mpsc::Sender<IoEvent>,
+        config: Config,
+        overrides: &Overrides,
+    ) -> Self {
         let actions = vec![Action::Quit].into();
         let is_loading = false;
+        let skip_menu = overrides.skips_menu();
+        let settings = config.merged(overrides);
+        let mode = settings.mode;
+        let themes = Theme::built_in();
+        let mut menu = Menu::new(mode);
+        menu.set_themes(themes.iter().map(|theme| theme.name.clone()).collect());
+        menu.select_theme(&settings.theme);
         let state = AppState::default();
 
         Self {
             io_tx,
             actions,
             is_loading,
+            mode,
+            text_id: overrides.text.clone(),
+            skip_menu,
+            menu,
+            heatmap: HeatmapSettings::default(),
+            config,
+            settings,
+            overall_stats: TypingStats::default(),
+            themes,
+            clock: Arc::new(SystemClock),
             state,
         }
     }
 
     pub async fn do_action(&mut self, key: Key) -> AppReturn {
-        if let Some(action) = self.actions.find(key) {
+        if let Some(action) = self.actions.find(key, &self.settings.keys) {
             debug!("Run action [{:?}]", action);
             match action {
                 Action::Quit => AppReturn::Exit,
-                Action::Typing(c) => {
-                    self.state.add_char(c);
+                Action::BackwardDeleteChar => {
+                    let events = self.state.input(Input::Backspace, self.now());
+                    self.handle_events(events).await;
                     AppReturn::Continue
                 }
-                Action::BackwardDeleteChar => {
-                    self.state.remove_char();
+                Action::TogglePause => {
+                    self.state.toggle_pause(self.now());
+                    AppReturn::Continue
+                }
+                Action::Up => {
+                    match &mut self.state {
+                        AppState::History { view } => view.previous_passage(),
+                        AppState::Settings { view } => view.up(),
+                        _ => self.menu.up(),
+                    }
+                    AppReturn::Continue
+                }
+                Action::Down => {
+                    match &mut self.state {
+                        AppState::History { view } => view.next_passage(),
+                        AppState::Settings { view } => view.down(),
+                        _ => self.menu.down(),
+                    }
+                    AppReturn::Continue
+                }
+                Action::Left => {
+                    match &mut self.state {
+                        AppState::History { view } => view.previous_mode(),
+                        AppState::Settings { view } => view.left(),
+                        _ => self.menu.left(),
+                    }
+                    AppReturn::Continue
+                }
+                Action::Right => {
+                    match &mut self.state {
+                        AppState::History { view } => view.next_mode(),
+                        AppState::Settings { view } => view.right(),
+                        _ => self.menu.right(),
+                    }
+                    AppReturn::Continue
+                }
+                Action::Select => self.select().await,
+                Action::Back => {
+                    self.open_menu();
+                    AppReturn::Continue
+                }
+                Action::Retry => {
+                    self.retry();
+                    AppReturn::Continue
+                }
+                Action::NextText => {
+                    self.dispatch(IoEvent::NextText).await;
+                    AppReturn::Continue
+                }
+                Action::SwitchHeatmap => {
+                    self.heatmap.next();
                     AppReturn::Continue
                 }
             }
+        } else if let Some(c) = self.typed_char(key) {
+            let events = self.state.input(Input::Char(c), self.now());
+            self.handle_events(events).await;
+            AppReturn::Continue
         } else {
             warn!("No action accociated to {}", key);
             AppReturn::Continue
         }
     }
 
-    pub async fn update_on_tick(&mut self) -> AppReturn {
-        self.state.increase_duaration_tick();
-        AppReturn::Continue
+    /// Any printable character is typed while a test is running, enter types
+    /// a line break.
+    fn typed_char(&self, key: Key) -> Option<char> {
+        if !self.state.is_typing() {
+            return None;
+        }
+        match key {
+            Key::Char(c) if !c.is_control() => Some(c),
+            Key::Enter => Some('\n'),
+            _ => None,
+        }
     }
 
-    pub async fn send_message_timeup(&mut self) -> AppReturn {
-        self.state.set_message_timeup();
-        AppReturn::Continue
+    async fn select(&mut self) -> AppReturn {
+        if let Some(view) = self.state.settings_mut() {
+            match view.selected() {
+                SettingsItem::Save => self.dispatch(IoEvent::SaveConfig).await,
+                _ => view.right(),
+            }
+            return AppReturn::Continue;
+        }
+        if !self.state.is_menu() {
+            self.open_menu();
+            return AppReturn::Continue;
+        }
+
+        match self.menu.selected() {
+            MenuItem::Start => {
+                self.mode = self.menu.mode();
+                self.text_id = self.menu.text_id().map(str::to_owned);
+                self.dispatch(IoEvent::StartTest).await;
+                AppReturn::Continue
+            }
+            MenuItem::History => {
+                self.dispatch(IoEvent::OpenHistory).await;
+                AppReturn::Continue
+            }
+            MenuItem::Settings => {
+                self.open_settings();
+                AppReturn::Continue
+            }
+            MenuItem::Quit => AppReturn::Exit,
+            _ => {
+                self.menu.right();
+                AppReturn::Continue
+            }
+        }
     }
 
-    pub async fn finished_text(&mut self) -> AppReturn {
-        self.state.set_message_finished();
-        self.state.stop_timer();
-        AppReturn::Continue
+    /// Shows the result once the test ends, and has the session saved.
+    async fn handle_events(&mut self, events: Vec<EngineEvent>) {
+        for event in events {
+            match event {
+                EngineEvent::WordCompleted { count } => debug!("{} words typed", count),
+                EngineEvent::Finished | EngineEvent::TimeUp if self.is_replaying() => {
+                    info!("Replay over");
+                    self.set_replay_actions();
+                }
+                EngineEvent::Finished | EngineEvent::TimeUp => {
+                    if let Some(result) = self.state.result() {
+                        info!("{}", result);
+                    }
+                    self.ended();
+                    self.dispatch(IoEvent::SaveSession).await;
+                }
+            }
+        }
+    }
+
+    fn ended(&mut self) {
+        self.actions = vec![
+            Action::Quit,
+            Action::Select,
+            Action::Back,
+            Action::Retry,
+            Action::NextText,
+            Action::SwitchHeatmap,
+        ]
+        .into();
+    }
+
+    /// Starts the same test again, on the same text, or plays the replay
+    /// again.
+    pub fn retry(&mut self) {
+        if let Some(state) = self.state.restarted() {
+            if self.is_replaying() {
+                self.set_replay_actions();
+            } else {
+                self.set_typing_actions();
+            }
+            self.state = state;
+        }
+    }
+
+    /// Plays a recorded session back, `speed` times faster than it was
+    /// typed.
+    pub fn play_replay(&mut self, replay: &Replay, speed: f64) {
+        info!("🎞 Replay of {} in {} mode", replay.passage_id, replay.mode);
+        self.mode = replay.mode;
+        self.set_replay_actions();
+        self.state = AppState::replaying(replay, speed);
+    }
+
+    pub fn is_replaying(&self) -> bool {
+        self.state.playback().is_some()
+    }
+
+    fn set_replay_actions(&mut self) {
+        self.actions = vec![
+            Action::Quit,
+            Action::Select,
+            Action::Back,
+            Action::Retry,
+            Action::SwitchHeatmap,
+        ]
+        .into();
     }
 
     pub async fn tick(&mut self) -> AppReturn {
+        let events = self.state.tick(self.now());
+        self.handle_events(events).await;
         AppReturn::Continue
     }
 
@@ -84,82 +312,192 @@ impl App {
         };
     }
 
+    /// Reads the time from the given clock instead of the system one.
+    pub fn set_clock(&mut self, clock: Arc<dyn Clock>) {
+        self.clock = clock;
+    }
+
+    /// The current time, which runs faster while playing a replay back.
+    pub fn now(&self) -> Instant {
+        let now = self.clock.now();
+        self.state
+            .playback()
+            .map_or(now, |playback| playback.now(now))
+    }
+
     pub fn state(&self) -> &AppState {
         &self.state
     }
 
+    pub fn mode(&self) -> TestMode {
+        self.mode
+    }
+
+    /// The text chosen in the menu, or `None` for a random one.
+    pub fn text_id(&self) -> Option<&str> {
+        self.text_id.as_deref()
+    }
+
+    pub fn skip_menu(&self) -> bool {
+        self.skip_menu
+    }
+
+    pub fn menu(&self) -> &Menu {
+        &self.menu
+    }
+
+    pub fn heatmap(&self) -> HeatmapSettings {
+        self.heatmap
+    }
+
+    pub fn keyboard_layout(&self) -> KeyboardLayout {
+        self.settings.keyboard_layout
+    }
+
+    /// The config file, without the command line settings.
+    pub fn config(&self) -> &Config {
+        &self.config
+    }
+
+    /// The settings in use.
+    pub fn settings(&self) -> &Config {
+        &self.settings
+    }
+
+    pub fn key_bindings(&self) -> &KeyBindings {
+        &self.settings.keys
+    }
+
+    /// Uses the settings saved from the settings screen, then goes back to
+    /// the menu. The tick rate and the texts directory apply from the next
+    /// start.
+    pub fn config_saved(&mut self, config: Config) {
+        self.mode = config.mode;
+        self.menu.select_theme(&config.theme);
+        self.settings = Config {
+            tick_rate_ms: self.settings.tick_rate_ms,
+            texts_dir: self.settings.texts_dir.clone(),
+            ..config.clone()
+        };
+        self.config = config;
+        self.open_menu();
+    }
+
+    /// Per-key statistics of every stored session.
+    pub fn overall_stats(&self) -> &TypingStats {
+        &self.overall_stats
+    }
+
+    pub fn set_overall_stats(&mut self, stats: TypingStats) {
+        self.overall_stats = stats;
+    }
+
+    /// The theme chosen in the menu.
+    pub fn theme(&self) -> &Theme {
+        self.themes
+            .get(self.menu.theme())
+            .unwrap_or(&self.themes[0])
+    }
+
+    /// Adds custom themes after the built-in ones. A custom theme replaces
+    /// the built-in one of the same name.
+    pub fn add_themes(&mut self, themes: Vec<Theme>) {
+        for theme in themes {
+            match self
+                .themes
+                .iter_mut()
+                .find(|known| known.name == theme.name)
+            {
+                Some(known) => *known = theme,
+                None => self.themes.push(theme),
+            }
+        }
+        let names = self.themes.iter().map(|theme| theme.name.clone()).collect();
+        self.menu.set_themes(names);
+    }
+
+    pub fn select_theme(&mut self, name: &str) -> bool {
+        self.menu.select_theme(name)
+    }
+
+    pub fn set_texts(&mut self, texts: &[TypingFileDisplay]) {
+        self.menu.set_texts(texts)
+    }
+
+    pub fn open_menu(&mut self) {
+        self.actions = vec![
+            Action::Quit,
+            Action::Up,
+            Action::Down,
+            Action::Left,
+            Action::Right,
+            Action::Select,
+        ]
+        .into();
+        self.menu.set_mode(self.mode);
+        self.state = AppState::Menu
+    }
+
+    /// Edits a copy of the config file, saved with `Save`.
+    pub fn open_settings(&mut self) {
+        self.actions = vec![
+            Action::Quit,
+            Action::Up,
+            Action::Down,
+            Action::Left,
+            Action::Right,
+            Action::Select,
+            Action::Back,
+        ]
+        .into();
+        let themes = self.themes.iter().map(|theme| theme.name.clone()).collect();
+        self.state = AppState::Settings {
+            view: SettingsView::new(self.config.clone(), themes),
+        }
+    }
+
+    /// Shows past sessions, filtered with the arrow keys.
+    pub fn show_history(&mut self, history: History) {
+        self.actions = vec![
+            Action::Quit,
+            Action::Up,
+            Action::Down,
+            Action::Left,
+            Action::Right,
+            Action::Select,
+            Action::Back,
+        ]
+        .into();
+        self.state = AppState::History {
+            view: HistoryView::new(history),
+        }
+    }
+
     pub fn is_loading(&self) -> bool {
         self.is_loading
     }
 
     pub fn initialized(&mut self) {
+        self.set_typing_actions();
+        self.state = AppState::initialized(self.mode)
+    }
+
+    fn set_typing_actions(&mut self) {
         self.actions = vec![
             Action::Quit,
-            Action::Typing('a'),
-            Action::Typing('b'),
-            Action::Typing('c'),
-            Action::Typing('d'),
-            Action::Typing('e'),
-            Action::Typing('f'),
-            Action::Typing('g'),
-            Action::Typing('h'),
-            Action::Typing('i'),
-            Action::Typing('j'),
-            Action::Typing('k'),
-            Action::Typing('l'),
-            Action::Typing('m'),
-            Action::Typing('n'),
-            Action::Typing('o'),
-            Action::Typing('p'),
-            Action::Typing('q'),
-            Action::Typing('r'),
-            Action::Typing('s'),
-            Action::Typing('t'),
-            Action::Typing('u'),
-            Action::Typing('v'),
-            Action::Typing('w'),
-            Action::Typing('x'),
-            Action::Typing('y'),
-            Action::Typing('z'),
-            Action::Typing('A'),
-            Action::Typing('B'),
-            Action::Typing('C'),
-            Action::Typing('D'),
-            Action::Typing('E'),
-            Action::Typing('F'),
-            Action::Typing('G'),
-            Action::Typing('H'),
-            Action::Typing('I'),
-            Action::Typing('J'),
-            Action::Typing('K'),
-            Action::Typing('L'),
-            Action::Typing('M'),
-            Action::Typing('N'),
-            Action::Typing('O'),
-            Action::Typing('P'),
-            Action::Typing('Q'),
-            Action::Typing('R'),
-            Action::Typing('S'),
-            Action::Typing('T'),
-            Action::Typing('U'),
-            Action::Typing('V'),
-            Action::Typing('W'),
-            Action::Typing('X'),
-            Action::Typing('Y'),
-            Action::Typing('Z'),
-            Action::Typing(' '),
-            Action::Typing('.'),
-            Action::Typing('\''),
-            Action::Typing('"'),
-            Action::Typing('('),
-            Action::Typing(')'),
-            Action::Typing('?'),
-            Action::Typing(','),
-            Action::Typing('-'),
             Action::BackwardDeleteChar,
+            Action::TogglePause,
+            Action::Back,
+            Action::Retry,
+            Action::NextText,
         ]
         .into();
-        self.state = AppState::initialized()
+    }
+
+    pub fn failed(&mut self, error: TextError) {
+        self.actions = vec![Action::Quit].into();
+        self.is_loading = false;
+        self.state = AppState::failed(error)
     }
 
     pub fn loaded(&mut self) {
@@ -181,4 +519,101 @@ impl App {
     pub fn set_words_count(&mut self, words_count: u32) {
         self.state.set_words_count(words_count)
     }
+
+    pub fn set_ghost(&mut self, ghost: Ghost) {
+        self.state.set_ghost(ghost)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use super::*;
+    use crate::engine::score::EndReason;
+
+    fn typing_app(to_type: &str) -> App {
+        let (io_tx, _io_rx) = tokio::sync::mpsc::channel(10);
+        let mut app = App::new(io_tx, Some(TestMode::Passage));
+        app.initialized();
+        app.load_text(to_type.to_owned());
+        app
+    }
+
+    #[tokio::test]
+    async fn should_type_any_printable_character() {
+        let text = "Déjà vu; 3: ¡Sí! 東京\nok";
+        let (io_tx, mut io_rx) = tokio::sync::mpsc::channel(10);
+        let mut app = App::new(io_tx, Some(TestMode::Passage));
+        app.initialized();
+        app.load_text(text.to_owned());
+
+        for c in text.chars() {
+            let key = if c == '\n' { Key::Enter } else { Key::Char(c) };
+            assert_eq!(app.do_action(key).await, AppReturn::Continue);
+        }
+
+        assert_eq!(app.state().typed_text(), Some(text.to_owned()));
+        assert_eq!(app.state().result().unwrap().reason, EndReason::Finished);
+        assert!(matches!(io_rx.try_recv(), Ok(IoEvent::SaveSession)));
+    }
+
+    #[tokio::test]
+    async fn should_run_control_actions_while_typing() {
+        let mut app = typing_app("ab");
+
+        app.do_action(Key::Char('a')).await;
+        app.do_action(Key::Backspace).await;
+        app.do_action(Key::Tab).await;
+        assert_eq!(app.state().typed_text(), Some("".to_owned()));
+
+        assert_eq!(app.do_action(Key::Ctrl('c')).await, AppReturn::Exit);
+    }
+
+    #[tokio::test]
+    async fn should_not_type_outside_of_a_test() {
+        let (io_tx, _io_rx) = tokio::sync::mpsc::channel(10);
+        let mut app = App::new(io_tx, None);
+        app.open_menu();
+
+        app.do_action(Key::Char('a')).await;
+        assert!(app.state().is_menu());
+        assert_eq!(app.state().typed_text(), None);
+    }
+
+    #[tokio::test]
+    async fn should_edit_and_save_settings() {
+        let (io_tx, mut io_rx) = tokio::sync::mpsc::channel(10);
+        let mut app = App::new(io_tx, None);
+        app.open_menu();
+        while app.menu().selected() != MenuItem::Settings {
+            app.do_action(Key::Down).await;
+        }
+        app.do_action(Key::Enter).await;
+
+        app.do_action(Key::Down).await;
+        app.do_action(Key::Right).await;
+        app.do_action(Key::Up).await;
+        app.do_action(Key::Up).await;
+        app.do_action(Key::Enter).await;
+        assert!(matches!(io_rx.try_recv(), Ok(IoEvent::SaveConfig)));
+
+        let config = app.state().settings().unwrap().config().clone();
+        assert_eq!(config.mode, TestMode::Timed(Duration::from_secs(120)));
+        app.config_saved(config);
+        assert!(app.state().is_menu());
+        assert_eq!(app.menu().mode(), TestMode::Timed(Duration::from_secs(120)));
+    }
+
+    #[tokio::test]
+    async fn should_use_bound_keys() {
+        let (io_tx, _io_rx) = tokio::sync::mpsc::channel(10);
+        let mut config = Config::default();
+        config.keys.bind(Action::Quit, vec![Key::Ctrl('q')]);
+        let mut app = App::with_config(io_tx, config, &Overrides::default());
+        app.open_menu();
+
+        assert_eq!(app.do_action(Key::Ctrl('c')).await, AppReturn::Continue);
+        assert_eq!(app.do_action(Key::Ctrl('q')).await, AppReturn::Exit);
+    }
 }
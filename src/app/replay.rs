@@ -0,0 +1,304 @@
+use std::time::{Duration, Instant};
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+use crate::engine::mode::TestMode;
+use crate::engine::score::{EndReason, Keystroke, TestResult};
+use crate::engine::{Engine, Input};
+use crate::io::file::TypingFileDisplay;
+
+/// An input of a recorded session, with its offset from the first one.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ReplayKeystroke {
+    /// Offset in microseconds.
+    pub at_us: u64,
+    pub input: Input,
+}
+
+impl ReplayKeystroke {
+    pub fn at(&self) -> Duration {
+        Duration::from_micros(self.at_us)
+    }
+}
+
+/// A finished or timed-out session, with everything needed to type it again
+/// the same way: the text, the mode and every input.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct Replay {
+    pub timestamp: DateTime<Utc>,
+    pub passage_id: String,
+    pub from: String,
+    pub url: String,
+    pub mode: TestMode,
+    pub reason: EndReason,
+    pub wpm: f64,
+    /// The text as it was to type, which for word-count tests is picked at
+    /// random.
+    pub text: String,
+    pub keystrokes: Vec<ReplayKeystroke>,
+}
+
+impl Replay {
+    /// Records the test of `engine`, or `None` while it is still running.
+    pub fn record(text: &TypingFileDisplay, engine: &Engine) -> Option<Self> {
+        let result = engine.result()?;
+        let keystrokes = engine
+            .keystrokes()
+            .iter()
+            .map(|keystroke| ReplayKeystroke {
+                at_us: keystroke.at().as_micros() as u64,
+                input: match keystroke {
+                    Keystroke::Typed { typed, .. } => Input::Char(*typed),
+                    Keystroke::Deleted { .. } => Input::Backspace,
+                },
+            })
+            .collect();
+
+        Some(Self {
+            timestamp: Utc::now(),
+            passage_id: text.id.clone(),
+            from: text.from.clone(),
+            url: text.url.clone(),
+            mode: engine.mode(),
+            reason: result.reason,
+            wpm: result.score.net_wpm,
+            text: engine.target().to_owned(),
+            keystrokes,
+        })
+    }
+
+    /// A finished passage run of "abc", each input given with its offset in
+    /// milliseconds, for tests.
+    #[cfg(test)]
+    pub fn sample(passage_id: &str, wpm: f64, inputs: &[(Input, u64)]) -> Self {
+        Self {
+            timestamp: Utc::now(),
+            passage_id: passage_id.to_owned(),
+            from: format!("Author {}", passage_id),
+            url: "".to_owned(),
+            mode: TestMode::Passage,
+            reason: EndReason::Finished,
+            wpm,
+            text: "abc".to_owned(),
+            keystrokes: inputs
+                .iter()
+                .map(|(input, at_ms)| ReplayKeystroke {
+                    at_us: at_ms * 1000,
+                    input: *input,
+                })
+                .collect(),
+        }
+    }
+
+    pub fn typing_information(&self) -> TypingFileDisplay {
+        TypingFileDisplay {
+            id: self.passage_id.clone(),
+            from: self.from.clone(),
+            content: self.text.clone(),
+            url: self.url.clone(),
+            words_count: self.text.split_whitespace().count() as u32,
+        }
+    }
+
+    /// A test on the same text and mode, where nothing is typed yet.
+    pub fn engine(&self) -> Engine {
+        Engine::new(self.mode, self.text.clone())
+    }
+
+    /// Types every input again, at once, and returns the result they give.
+    /// It matches the recorded one to the microsecond.
+    pub fn result(&self) -> Option<TestResult> {
+        let start = Instant::now();
+        let mut engine = self.engine();
+        for keystroke in &self.keystrokes {
+            engine.input(keystroke.input, start + keystroke.at());
+        }
+        if let Some(limit) = self.mode.time_limit() {
+            engine.tick(start + limit);
+        }
+        engine.result().copied()
+    }
+}
+
+/// Plays the inputs of a replay back, `speed` times faster than they were
+/// typed. The playback starts with the first call to `due`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Playback {
+    keystrokes: Vec<ReplayKeystroke>,
+    speed: f64,
+    started_at: Option<Instant>,
+    next: usize,
+}
+
+impl Playback {
+    pub fn new(replay: &Replay, speed: f64) -> Self {
+        Self {
+            keystrokes: replay.keystrokes.clone(),
+            speed,
+            started_at: None,
+            next: 0,
+        }
+    }
+
+    /// The same playback, from the start.
+    pub fn restarted(&self) -> Self {
+        Self {
+            started_at: None,
+            next: 0,
+            ..self.clone()
+        }
+    }
+
+    pub fn speed(&self) -> f64 {
+        self.speed
+    }
+
+    pub fn is_done(&self) -> bool {
+        self.next >= self.keystrokes.len()
+    }
+
+    /// The time in the replay at `now`, which goes `speed` times faster once
+    /// the playback is started.
+    pub fn now(&self, now: Instant) -> Instant {
+        match self.started_at {
+            Some(started_at) => {
+                started_at
+                    + now
+                        .saturating_duration_since(started_at)
+                        .mul_f64(self.speed)
+            }
+            None => now,
+        }
+    }
+
+    /// The inputs typed up to `now`, in replay time, with the instant each
+    /// one was typed at.
+    pub fn due(&mut self, now: Instant) -> Vec<(Input, Instant)> {
+        let started_at = *self.started_at.get_or_insert(now);
+        let mut due = vec![];
+        while let Some(keystroke) = self.keystrokes.get(self.next) {
+            let at = started_at + keystroke.at();
+            if at > now {
+                break;
+            }
+            due.push((keystroke.input, at));
+            self.next += 1;
+        }
+        due
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn text() -> TypingFileDisplay {
+        TypingFileDisplay {
+            id: "7".to_owned(),
+            from: "Somewhere".to_owned(),
+            content: "one two".to_owned(),
+            url: "".to_owned(),
+            words_count: 2,
+        }
+    }
+
+    fn recorded(mode: TestMode, inputs: &[(Input, u64)]) -> Replay {
+        let start = Instant::now();
+        let mut engine = Engine::new(mode, "one two".to_owned());
+        for (input, at) in inputs {
+            engine.input(*input, start + Duration::from_millis(*at));
+        }
+        engine.tick(start + Duration::from_secs(60));
+        Replay::record(&text(), &engine).unwrap()
+    }
+
+    #[test]
+    fn should_record_every_input() {
+        let inputs = "one tx"
+            .chars()
+            .enumerate()
+            .map(|(i, c)| (Input::Char(c), 150 * i as u64))
+            .chain([(Input::Backspace, 1000)])
+            .collect::<Vec<_>>();
+        let replay = recorded(TestMode::Timed(Duration::from_secs(30)), &inputs);
+
+        assert_eq!(replay.keystrokes.len(), 7);
+        assert_eq!(
+            replay.keystrokes[6],
+            ReplayKeystroke {
+                at_us: 1_000_000,
+                input: Input::Backspace
+            }
+        );
+        assert_eq!(replay.reason, EndReason::TimeUp);
+
+        let json = serde_json::to_string(&replay.keystrokes[5..]).unwrap();
+        assert_eq!(
+            json,
+            r#"[{"at_us":750000,"input":{"char":"x"}},{"at_us":1000000,"input":"backspace"}]"#
+        );
+    }
+
+    #[test]
+    fn should_give_the_recorded_result() {
+        let inputs = "one two"
+            .chars()
+            .enumerate()
+            .map(|(i, c)| (Input::Char(c), 210 * i as u64))
+            .collect::<Vec<_>>();
+        let replay = recorded(TestMode::Passage, &inputs);
+
+        let result = replay.result().unwrap();
+        assert_eq!(result.reason, EndReason::Finished);
+        assert_eq!(result.score.elapsed, Duration::from_millis(1260));
+        assert_eq!(result.score.net_wpm, replay.wpm);
+        assert!(Replay::record(&text(), &replay.engine()).is_none());
+    }
+
+    #[test]
+    fn should_keep_sub_millisecond_offsets() {
+        let start = Instant::now();
+        let mut engine = Engine::new(TestMode::Passage, "one two".to_owned());
+        for (i, c) in "one two".chars().enumerate() {
+            engine.input(
+                Input::Char(c),
+                start + Duration::from_micros(1_999 * i as u64),
+            );
+        }
+        let replay = Replay::record(&text(), &engine).unwrap();
+        assert_eq!(replay.keystrokes[6].at(), Duration::from_micros(11_994));
+
+        let result = replay.result().unwrap();
+        assert_eq!(result.score.elapsed, engine.result().unwrap().score.elapsed);
+        assert_eq!(result.score.net_wpm, replay.wpm);
+    }
+
+    #[test]
+    fn should_play_back_faster() {
+        let replay = recorded(
+            TestMode::Timed(Duration::from_secs(15)),
+            &[
+                (Input::Char('o'), 0),
+                (Input::Char('n'), 400),
+                (Input::Char('e'), 1000),
+            ],
+        );
+        let start = Instant::now();
+        let mut playback = Playback::new(&replay, 2.0);
+        assert_eq!(playback.now(start), start);
+
+        assert_eq!(playback.due(start), vec![(Input::Char('o'), start)]);
+        let now = playback.now(start + Duration::from_millis(300));
+        assert_eq!(now, start + Duration::from_millis(600));
+        assert_eq!(
+            playback.due(now),
+            vec![(Input::Char('n'), start + Duration::from_millis(400))]
+        );
+        assert!(!playback.is_done());
+
+        playback.due(playback.now(start + Duration::from_millis(500)));
+        assert!(playback.is_done());
+        assert!(!playback.restarted().is_done());
+    }
+}
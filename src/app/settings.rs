@@ -0,0 +1,202 @@
+use std::time::Duration;
+
+use crate::app::config::Config;
+use crate::app::cycle::{cycle, next, previous};
+use crate::app::ghost::GhostRun;
+use crate::app::heatmap::KeyboardLayout;
+use crate::engine::mode::{TestMode, DURATIONS, WORD_GOALS};
+
+/// Tick rates offered on the settings screen, in milliseconds.
+const TICK_RATES: [u64; 5] = [50, 100, 200, 250, 500];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SettingsItem {
+    TickRate,
+    Mode,
+    Theme,
+    KeyboardLayout,
+    Ghost,
+    Save,
+}
+
+impl SettingsItem {
+    pub const ALL: [SettingsItem; 6] = [
+        SettingsItem::TickRate,
+        SettingsItem::Mode,
+        SettingsItem::Theme,
+        SettingsItem::KeyboardLayout,
+        SettingsItem::Ghost,
+        SettingsItem::Save,
+    ];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            SettingsItem::TickRate => "Tick rate",
+            SettingsItem::Mode => "Mode",
+            SettingsItem::Theme => "Theme",
+            SettingsItem::KeyboardLayout => "Keyboard",
+            SettingsItem::Ghost => "Ghost",
+            SettingsItem::Save => "Save",
+        }
+    }
+}
+
+/// The config being edited on the settings screen. Items are chosen with up
+/// and down, and their value is changed with left and right, among the
+/// offered values and the one already in the config.
+#[derive(Debug, Clone)]
+pub struct SettingsView {
+    config: Config,
+    selected: usize,
+    tick_rates: Vec<u64>,
+    modes: Vec<TestMode>,
+    themes: Vec<String>,
+}
+
+impl SettingsView {
+    pub fn new(config: Config, themes: Vec<String>) -> Self {
+        let mut tick_rates = TICK_RATES.to_vec();
+        if !tick_rates.contains(&config.tick_rate_ms) {
+            tick_rates.push(config.tick_rate_ms);
+            tick_rates.sort_unstable();
+        }
+
+        let mut modes = DURATIONS
+            .iter()
+            .map(|seconds| TestMode::Timed(Duration::from_secs(*seconds)))
+            .chain(WORD_GOALS.iter().map(|goal| TestMode::Words(*goal)))
+            .chain([TestMode::Passage])
+            .collect::<Vec<_>>();
+        if !modes.contains(&config.mode) {
+            modes.insert(0, config.mode);
+        }
+
+        let mut themes = themes;
+        if !themes.contains(&config.theme) {
+            themes.push(config.theme.clone());
+        }
+
+        Self {
+            config,
+            selected: 0,
+            tick_rates,
+            modes,
+            themes,
+        }
+    }
+
+    pub fn config(&self) -> &Config {
+        &self.config
+    }
+
+    pub fn selected(&self) -> SettingsItem {
+        SettingsItem::ALL[self.selected]
+    }
+
+    pub fn up(&mut self) {
+        self.selected = previous(self.selected, SettingsItem::ALL.len());
+    }
+
+    pub fn down(&mut self) {
+        self.selected = next(self.selected, SettingsItem::ALL.len());
+    }
+
+    pub fn left(&mut self) {
+        self.change(previous);
+    }
+
+    pub fn right(&mut self) {
+        self.change(next);
+    }
+
+    fn change(&mut self, step: fn(usize, usize) -> usize) {
+        let selected = self.selected();
+        let config = &mut self.config;
+        match selected {
+            SettingsItem::TickRate => {
+                config.tick_rate_ms = cycle(&self.tick_rates, &config.tick_rate_ms, step)
+            }
+            SettingsItem::Mode => config.mode = cycle(&self.modes, &config.mode, step),
+            SettingsItem::Theme => config.theme = cycle(&self.themes, &config.theme, step),
+            SettingsItem::KeyboardLayout => {
+                config.keyboard_layout = cycle(&KeyboardLayout::ALL, &config.keyboard_layout, step)
+            }
+            SettingsItem::Ghost => config.ghost = cycle(&GhostRun::ALL, &config.ghost, step),
+            SettingsItem::Save => {}
+        }
+    }
+
+    pub fn value(&self, item: SettingsItem) -> String {
+        match item {
+            SettingsItem::TickRate => format!("{} ms (on restart)", self.config.tick_rate_ms),
+            SettingsItem::Mode => self.config.mode.label(),
+            SettingsItem::Theme => self.config.theme.clone(),
+            SettingsItem::KeyboardLayout => self.config.keyboard_layout.to_string(),
+            SettingsItem::Ghost => match self.config.ghost {
+                GhostRun::Off => "off".to_owned(),
+                GhostRun::Best => "personal best".to_owned(),
+                GhostRun::Last => "last attempt".to_owned(),
+            },
+            SettingsItem::Save => String::new(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn view() -> SettingsView {
+        let themes = vec!["default".to_owned(), "light".to_owned()];
+        SettingsView::new(Config::default(), themes)
+    }
+
+    #[test]
+    fn should_change_settings() {
+        let mut view = view();
+
+        view.right();
+        assert_eq!(view.config().tick_rate_ms, 200);
+
+        view.down();
+        view.left();
+        assert_eq!(view.config().mode, TestMode::Timed(Duration::from_secs(30)));
+
+        view.down();
+        view.left();
+        assert_eq!(view.config().theme, "light");
+
+        view.down();
+        view.right();
+        assert_eq!(view.value(SettingsItem::KeyboardLayout), "azerty");
+
+        view.down();
+        view.left();
+        assert_eq!(view.config().ghost, GhostRun::Last);
+        assert_eq!(view.value(SettingsItem::Ghost), "last attempt");
+    }
+
+    #[test]
+    fn should_keep_values_not_offered() {
+        let config = Config {
+            tick_rate_ms: 75,
+            mode: TestMode::Words(42),
+            theme: "amber".to_owned(),
+            ..Config::default()
+        };
+        let mut view = SettingsView::new(config, vec!["default".to_owned()]);
+
+        view.right();
+        view.left();
+        assert_eq!(view.config().tick_rate_ms, 75);
+
+        view.down();
+        view.left();
+        view.right();
+        assert_eq!(view.value(SettingsItem::Mode), "42 words");
+
+        view.down();
+        view.right();
+        assert_eq!(view.config().theme, "default");
+    }
+}
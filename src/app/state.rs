@@ -1,29 +1,47 @@
-use std::time::Duration;
+use std::time::{Duration, Instant};
 
-use crate::io::file::TypingFileDisplay;
+use crate::app::ghost::Ghost;
+use crate::app::history::HistoryView;
+use crate::app::replay::{Playback, Replay};
+use crate::app::settings::SettingsView;
+use crate::engine::mode::TestMode;
+use crate::engine::score::{Score, TestResult};
+use crate::engine::stats::TypingStats;
+use crate::engine::{Engine, EngineEvent, Input};
+use crate::io::file::{TextError, TypingFileDisplay};
 
-#[derive(Clone)]
+// There is a single state for the whole application, its size does not matter.
+#[allow(clippy::large_enum_variant)]
+#[derive(Clone, Default)]
 pub enum AppState {
+    #[default]
     Init,
     Initialized {
-        duration: Duration,
-        counter_tick: u64,
-        typed_text: String,
-        to_type: String,
+        engine: Engine,
         words_count: u32,
         typing_information: TypingFileDisplay,
+        /// Types the inputs of a replay instead of the player.
+        playback: Option<Playback>,
+        /// An earlier run of the text, raced by the player.
+        ghost: Option<Ghost>,
     },
     Menu,
+    History {
+        view: HistoryView,
+    },
+    Settings {
+        view: SettingsView,
+    },
+    Error {
+        error: TextError,
+    },
 }
 
 impl AppState {
-    pub fn initialized() -> Self {
-        let duration = Duration::from_secs(1);
-        let counter_tick = 0;
-        let typed_text = "".to_owned();
-        let to_type = "".to_owned();
+    pub fn initialized(mode: TestMode) -> Self {
         let words_count = 0;
         let typing_information = TypingFileDisplay {
+            id: "".to_owned(),
             from: "".to_owned(),
             content: "".to_owned(),
             url: "".to_owned(),
@@ -31,85 +49,216 @@ impl AppState {
         };
 
         Self::Initialized {
-            duration,
-            counter_tick,
-            typed_text,
-            to_type,
+            engine: Engine::new(mode, "".to_owned()),
             words_count,
             typing_information,
+            playback: None,
+            ghost: None,
         }
     }
 
-    pub fn is_initialized(&self) -> bool {
-        matches!(self, &Self::Initialized { .. })
+    /// Plays `replay` back, `speed` times faster than it was typed.
+    pub fn replaying(replay: &Replay, speed: f64) -> Self {
+        let typing_information = replay.typing_information();
+
+        Self::Initialized {
+            engine: replay.engine(),
+            words_count: typing_information.words_count,
+            typing_information,
+            playback: Some(Playback::new(replay, speed)),
+            ghost: None,
+        }
+    }
+
+    /// A fresh state for the same mode and text, or `None` when no test is
+    /// in progress.
+    pub fn restarted(&self) -> Option<Self> {
+        if let Self::Initialized {
+            engine,
+            words_count,
+            typing_information,
+            playback,
+            ghost,
+        } = self
+        {
+            Some(Self::Initialized {
+                engine: engine.restarted(),
+                words_count: *words_count,
+                typing_information: typing_information.to_owned(),
+                playback: playback.as_ref().map(Playback::restarted),
+                ghost: ghost.clone(),
+            })
+        } else {
+            None
+        }
+    }
+
+    pub fn failed(error: TextError) -> Self {
+        Self::Error { error }
+    }
+
+    pub fn error(&self) -> Option<&TextError> {
+        if let Self::Error { error } = self {
+            Some(error)
+        } else {
+            None
+        }
+    }
+
+    pub fn history(&self) -> Option<&HistoryView> {
+        if let Self::History { view } = self {
+            Some(view)
+        } else {
+            None
+        }
     }
 
-    pub fn count_tick(&self) -> Option<u64> {
-        if let Self::Initialized { counter_tick, .. } = self {
-            Some(*counter_tick)
+    pub fn history_mut(&mut self) -> Option<&mut HistoryView> {
+        if let Self::History { view } = self {
+            Some(view)
         } else {
             None
         }
     }
 
-    pub fn increase_duaration_tick(&mut self) {
-        if let Self::Initialized { duration, .. } = self {
-            *duration = duration.checked_add(Duration::from_secs(1)).unwrap();
+    pub fn settings(&self) -> Option<&SettingsView> {
+        if let Self::Settings { view } = self {
+            Some(view)
+        } else {
+            None
         }
     }
 
-    pub fn duration(&self) -> Option<&Duration> {
-        if let Self::Initialized { duration, .. } = self {
-            Some(duration)
+    pub fn settings_mut(&mut self) -> Option<&mut SettingsView> {
+        if let Self::Settings { view } = self {
+            Some(view)
         } else {
             None
         }
     }
 
-    pub fn is_finished(&self) -> bool {
+    pub fn is_menu(&self) -> bool {
+        matches!(self, &Self::Menu)
+    }
+
+    /// A test is running, and accepts typed characters.
+    pub fn is_typing(&self) -> bool {
+        self.playback().is_none() && self.engine().is_some_and(Engine::is_running)
+    }
+
+    pub fn playback(&self) -> Option<&Playback> {
         if let Self::Initialized {
-            typed_text,
-            to_type,
+            playback: Some(playback),
             ..
         } = self
         {
-            typed_text == to_type
+            Some(playback)
         } else {
-            false
+            None
         }
     }
 
-    pub fn is_time_over(&self) -> bool {
-        if let Self::Initialized { duration, .. } = self {
-            *duration >= Duration::from_secs(60)
+    pub fn ghost(&self) -> Option<&Ghost> {
+        if let Self::Initialized {
+            ghost: Some(ghost), ..
+        } = self
+        {
+            Some(ghost)
         } else {
-            false
+            None
         }
     }
 
-    pub fn typed_text(&self) -> Option<String> {
-        if let Self::Initialized { typed_text, .. } = self {
-            Some(typed_text.to_owned())
+    pub fn set_ghost(&mut self, ghost: Ghost) {
+        if let Self::Initialized {
+            ghost: ghost_mut, ..
+        } = self
+        {
+            *ghost_mut = Some(ghost);
+        }
+    }
+
+    /// Position of the cursor of the ghost, as far into its run as the
+    /// player is into the test.
+    pub fn ghost_position(&self, now: Instant) -> Option<usize> {
+        let engine = self.engine()?;
+        self.ghost()
+            .map(|ghost| ghost.position(engine.elapsed(now)))
+    }
+
+    /// Number of characters the player is ahead of the ghost, negative when
+    /// behind.
+    pub fn ghost_lead(&self, now: Instant) -> Option<i64> {
+        let engine = self.engine()?;
+        self.ghost_position(now)
+            .map(|position| engine.cursor() as i64 - position as i64)
+    }
+
+    pub fn is_initialized(&self) -> bool {
+        matches!(self, &Self::Initialized { .. })
+    }
+
+    /// The test in progress, or just ended.
+    pub fn engine(&self) -> Option<&Engine> {
+        if let Self::Initialized { engine, .. } = self {
+            Some(engine)
         } else {
             None
         }
     }
 
-    pub fn to_type(&self) -> Option<Vec<&str>> {
-        if let Self::Initialized { to_type, .. } = self {
-            Some(to_type.split("/").collect::<Vec<&str>>())
+    fn engine_mut(&mut self) -> Option<&mut Engine> {
+        if let Self::Initialized { engine, .. } = self {
+            Some(engine)
         } else {
             None
         }
     }
 
+    pub fn elapsed(&self, now: Instant) -> Option<Duration> {
+        self.engine().map(|engine| engine.elapsed(now))
+    }
+
+    pub fn mode(&self) -> Option<TestMode> {
+        self.engine().map(Engine::mode)
+    }
+
+    pub fn remaining(&self, now: Instant) -> Option<Duration> {
+        self.engine().and_then(|engine| engine.remaining(now))
+    }
+
+    /// Number of words typed so far, and number of words to type.
+    pub fn words_progress(&self) -> Option<(usize, usize)> {
+        self.engine().map(Engine::words_progress)
+    }
+
+    /// Ratio of the target text already typed, between 0 and 1.
+    pub fn text_progress(&self) -> Option<f64> {
+        self.engine().map(Engine::text_progress)
+    }
+
+    pub fn is_paused(&self) -> bool {
+        self.engine().is_some_and(Engine::is_paused)
+    }
+
+    pub fn toggle_pause(&mut self, now: Instant) {
+        if let Some(engine) = self.engine_mut() {
+            engine.toggle_pause(now);
+        }
+    }
+
+    pub fn typed_text(&self) -> Option<String> {
+        self.engine().map(|engine| engine.typed().to_owned())
+    }
+
+    pub fn to_type(&self) -> Option<&str> {
+        self.engine().map(Engine::target)
+    }
+
+    /// Starts the test over, on the given text.
     pub fn set_to_type(&mut self, to_type: String) {
-        if let Self::Initialized {
-            to_type: to_type_mut,
-            ..
-        } = self
-        {
-            *to_type_mut = to_type;
+        if let Some(engine) = self.engine_mut() {
+            *engine = Engine::new(engine.mode(), to_type);
         }
     }
 
@@ -134,44 +283,51 @@ impl AppState {
         }
     }
 
-    pub fn add_char(&mut self, c: &char) {
-        if let Self::Initialized { typed_text, .. } = self {
-            typed_text.push(*c);
-        }
+    /// Types or deletes a character in the test in progress.
+    pub fn input(&mut self, input: Input, now: Instant) -> Vec<EngineEvent> {
+        self.engine_mut()
+            .map(|engine| engine.input(input, now))
+            .unwrap_or_default()
     }
 
-    pub fn remove_char(&mut self) {
-        if let Self::Initialized { typed_text, .. } = self {
-            typed_text.pop();
-        }
-    }
-
-    pub fn set_message_timeup(&mut self) {
-        if let Self::Initialized { to_type, .. } = self {
-            *to_type = "Time is up!".to_owned();
-        }
-    }
-
-    pub fn set_message_finished(&mut self) {
+    /// Lets the time of the test in progress go by, typing the inputs of
+    /// the replay due by then.
+    pub fn tick(&mut self, now: Instant) -> Vec<EngineEvent> {
         if let Self::Initialized {
-            to_type,
-            duration,
-            words_count,
+            engine,
+            playback: Some(playback),
             ..
         } = self
         {
-            let result_text = format!(
-                "Finished! Your speed is {:?} WPM",
-                60 * *words_count as u64 / duration.as_secs()
-            );
-            *to_type = result_text;
+            let mut events = playback
+                .due(now)
+                .into_iter()
+                .flat_map(|(input, at)| engine.input(input, at))
+                .collect::<Vec<_>>();
+            events.extend(engine.tick(now));
+            return events;
         }
+
+        self.engine_mut()
+            .map(|engine| engine.tick(now))
+            .unwrap_or_default()
     }
 
-    pub fn stop_timer(&mut self) {
-        if let Self::Initialized { duration, .. } = self {
-            *duration = Duration::from_secs(1);
-        }
+    pub fn score(&self, now: Instant) -> Option<Score> {
+        self.engine().map(|engine| engine.score(now))
+    }
+
+    pub fn wpm_samples(&self) -> &[u64] {
+        self.engine().map_or(&[], Engine::wpm_samples)
+    }
+
+    /// Per-key and per-bigram statistics of the keystrokes so far.
+    pub fn typing_stats(&self) -> Option<TypingStats> {
+        self.engine().map(Engine::typing_stats)
+    }
+
+    pub fn result(&self) -> Option<&TestResult> {
+        self.engine().and_then(Engine::result)
     }
 
     pub fn set_words_count(&mut self, wc: u32) {
@@ -181,8 +337,103 @@ impl AppState {
     }
 }
 
-impl Default for AppState {
-    fn default() -> Self {
-        Self::Init
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::app::ghost::GhostRun;
+    use crate::engine::score::EndReason;
+
+    fn type_text(state: &mut AppState, text: &str) -> Vec<EngineEvent> {
+        text.chars()
+            .flat_map(|c| state.input(Input::Char(c), Instant::now()))
+            .collect()
+    }
+
+    #[test]
+    fn should_end_when_cursor_reaches_the_end() {
+        let mut state = AppState::initialized(TestMode::Passage);
+        state.set_to_type("ab".to_owned());
+
+        type_text(&mut state, "a");
+        assert!(state.is_typing());
+
+        assert!(type_text(&mut state, "x").contains(&EngineEvent::Finished));
+        assert!(!state.is_typing());
+        assert_eq!(state.result().unwrap().reason, EndReason::Finished);
+    }
+
+    #[test]
+    fn should_track_progress_through_the_text() {
+        let mut state = AppState::initialized(TestMode::Passage);
+        state.set_to_type("one two three".to_owned());
+        assert_eq!(state.words_progress(), Some((0, 3)));
+
+        type_text(&mut state, "one tw");
+        assert_eq!(state.words_progress(), Some((1, 3)));
+        assert_eq!(state.text_progress(), Some(6.0 / 13.0));
+
+        type_text(&mut state, "o threx");
+        assert_eq!(state.words_progress(), Some((3, 3)));
+        assert_eq!(state.text_progress(), Some(1.0));
+    }
+
+    #[test]
+    fn should_stop_typing_once_ended() {
+        let mut state = AppState::initialized(TestMode::Words(1));
+        state.set_to_type("a".to_owned());
+        type_text(&mut state, "a");
+
+        assert!(type_text(&mut state, "b").is_empty());
+        assert!(state.input(Input::Backspace, Instant::now()).is_empty());
+        assert_eq!(state.typed_text(), Some("a".to_owned()));
+    }
+
+    #[test]
+    fn should_restart_with_same_text() {
+        let mut state = AppState::initialized(TestMode::Passage);
+        state.set_to_type("ab".to_owned());
+        type_text(&mut state, "ab");
+
+        let state = state.restarted().unwrap();
+        assert_eq!(state.to_type(), Some("ab"));
+        assert_eq!(state.typed_text(), Some("".to_owned()));
+        assert_eq!(state.elapsed(Instant::now()), Some(Duration::ZERO));
+        assert!(state.result().is_none());
+        assert!(AppState::Menu.restarted().is_none());
+    }
+
+    #[test]
+    fn should_race_the_ghost() {
+        // The ghost types a character every second.
+        let start = Instant::now();
+        let at = |millis| start + Duration::from_millis(millis);
+        let mut earlier = Engine::new(TestMode::Passage, "abcd".to_owned());
+        for (index, c) in "abcd".chars().enumerate() {
+            earlier.input(Input::Char(c), at(index as u64 * 1000));
+        }
+        let mut state = AppState::initialized(TestMode::Passage);
+        state.set_to_type("abcd".to_owned());
+        let replay = Replay::record(&state.typing_information().unwrap(), &earlier).unwrap();
+        state.set_ghost(Ghost::new(GhostRun::Best, &replay));
+        assert_eq!(state.ghost_lead(start), Some(0));
+
+        state.input(Input::Char('a'), at(0));
+        state.input(Input::Char('b'), at(500));
+        assert_eq!(state.ghost_position(at(500)), Some(1));
+        assert_eq!(state.ghost_lead(at(500)), Some(1));
+        assert_eq!(state.ghost_lead(at(2500)), Some(-1));
+
+        let state = state.restarted().unwrap();
+        assert!(state.ghost().is_some());
+        assert_eq!(state.ghost_lead(start), Some(0));
+    }
+
+    #[test]
+    fn should_not_type_outside_of_a_test() {
+        let mut state = AppState::Menu;
+
+        assert!(state.input(Input::Char('a'), Instant::now()).is_empty());
+        assert!(state.tick(Instant::now()).is_empty());
+        assert_eq!(state.typed_text(), None);
     }
 }
@@ -0,0 +1,343 @@
+use std::collections::BTreeMap;
+
+use eyre::{eyre, Result, WrapErr};
+use tui::style::Color;
+
+/// Colour of every role in the interface.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Theme {
+    pub name: String,
+    pub text: Color,
+    pub background: Color,
+    pub muted: Color,
+    pub border: Color,
+    pub title: Color,
+    pub highlight: Color,
+    pub source: Color,
+    pub url: Color,
+    pub correct: Color,
+    pub incorrect: Color,
+    pub incorrect_text: Color,
+    pub pending: Color,
+    pub cursor: Color,
+    pub cursor_text: Color,
+    /// Cursor of the earlier run raced during tests.
+    pub ghost: Color,
+    pub gauge: Color,
+    pub chart: Color,
+    pub error: Color,
+    pub warning: Color,
+    /// Heatmap colours, from the best keys to the worst ones.
+    pub scale: [Color; 4],
+    pub no_data: Color,
+    pub key_text: Color,
+    pub log_error: Color,
+    pub log_warn: Color,
+    pub log_info: Color,
+    pub log_debug: Color,
+    pub log_trace: Color,
+}
+
+impl Default for Theme {
+    fn default() -> Self {
+        Self {
+            name: "default".to_owned(),
+            text: Color::White,
+            background: Color::Black,
+            muted: Color::DarkGray,
+            border: Color::White,
+            title: Color::LightCyan,
+            highlight: Color::LightCyan,
+            source: Color::Yellow,
+            url: Color::Blue,
+            correct: Color::Green,
+            incorrect: Color::Red,
+            incorrect_text: Color::White,
+            pending: Color::DarkGray,
+            cursor: Color::White,
+            cursor_text: Color::Black,
+            ghost: Color::Magenta,
+            gauge: Color::Cyan,
+            chart: Color::Cyan,
+            error: Color::Red,
+            warning: Color::Yellow,
+            scale: [Color::Green, Color::Yellow, Color::LightRed, Color::Red],
+            no_data: Color::DarkGray,
+            key_text: Color::Black,
+            log_error: Color::Red,
+            log_warn: Color::Yellow,
+            log_info: Color::Blue,
+            log_debug: Color::Green,
+            log_trace: Color::Gray,
+        }
+    }
+}
+
+impl Theme {
+    /// Themes shipped with jackdull, the default one first.
+    pub fn built_in() -> Vec<Theme> {
+        vec![
+            Theme::default(),
+            Theme::high_contrast(),
+            Theme::colour_blind(),
+            Theme::light(),
+        ]
+    }
+
+    /// Pure black and white with bright accents, for low-vision players and
+    /// poor displays.
+    pub fn high_contrast() -> Self {
+        Self {
+            name: "high-contrast".to_owned(),
+            text: Color::White,
+            background: Color::Black,
+            muted: Color::Gray,
+            border: Color::White,
+            title: Color::White,
+            highlight: Color::LightYellow,
+            source: Color::LightYellow,
+            url: Color::LightCyan,
+            correct: Color::White,
+            incorrect: Color::LightRed,
+            incorrect_text: Color::Black,
+            pending: Color::Gray,
+            cursor: Color::LightYellow,
+            cursor_text: Color::Black,
+            ghost: Color::LightMagenta,
+            gauge: Color::LightYellow,
+            chart: Color::LightYellow,
+            error: Color::LightRed,
+            warning: Color::LightYellow,
+            scale: [
+                Color::White,
+                Color::LightYellow,
+                Color::LightMagenta,
+                Color::LightRed,
+            ],
+            no_data: Color::DarkGray,
+            key_text: Color::Black,
+            log_error: Color::LightRed,
+            log_warn: Color::LightYellow,
+            log_info: Color::White,
+            log_debug: Color::Gray,
+            log_trace: Color::Gray,
+        }
+    }
+
+    /// Blue and orange instead of green and red, from the Okabe-Ito palette,
+    /// which stay apart for every common colour vision deficiency.
+    pub fn colour_blind() -> Self {
+        let blue = Color::Rgb(0, 114, 178);
+        let sky = Color::Rgb(86, 180, 233);
+        let orange = Color::Rgb(230, 159, 0);
+        let vermillion = Color::Rgb(213, 94, 0);
+        let yellow = Color::Rgb(240, 228, 66);
+        let purple = Color::Rgb(204, 121, 167);
+
+        Self {
+            name: "colour-blind".to_owned(),
+            title: sky,
+            highlight: sky,
+            source: yellow,
+            url: sky,
+            correct: sky,
+            incorrect: vermillion,
+            ghost: purple,
+            gauge: blue,
+            chart: sky,
+            error: vermillion,
+            warning: orange,
+            scale: [blue, sky, orange, vermillion],
+            log_error: vermillion,
+            log_warn: orange,
+            log_info: sky,
+            log_debug: blue,
+            ..Theme::default()
+        }
+    }
+
+    /// Dark text for terminals with a light background.
+    pub fn light() -> Self {
+        Self {
+            name: "light".to_owned(),
+            text: Color::Black,
+            background: Color::Reset,
+            muted: Color::Gray,
+            border: Color::DarkGray,
+            title: Color::Blue,
+            highlight: Color::Blue,
+            source: Color::Magenta,
+            url: Color::Blue,
+            correct: Color::Green,
+            incorrect: Color::Red,
+            incorrect_text: Color::White,
+            pending: Color::Gray,
+            cursor: Color::Black,
+            cursor_text: Color::White,
+            gauge: Color::Blue,
+            chart: Color::Blue,
+            log_info: Color::Blue,
+            log_trace: Color::DarkGray,
+            ..Theme::default()
+        }
+    }
+
+    /// Parses a theme from TOML, where each key is a role and each value a
+    /// colour name or `#rrggbb`. Roles that are not given keep the colour of
+    /// the `base` built-in theme, or of the default one.
+    pub fn from_toml(name: &str, content: &str) -> Result<Self> {
+        let values: BTreeMap<String, String> =
+            toml::from_str(content).wrap_err("expected role = \"colour\" pairs")?;
+
+        let mut theme = match values.get("base") {
+            Some(base) => Theme::built_in()
+                .into_iter()
+                .find(|theme| theme.name == *base)
+                .ok_or_else(|| eyre!("unknown base theme '{}'", base))?,
+            None => Theme::default(),
+        };
+        theme.name = name.to_owned();
+
+        for (role, value) in values.iter().filter(|(role, _)| *role != "base") {
+            let colour = parse_colour(value).wrap_err_with(|| format!("invalid {}", role))?;
+            let slot = theme
+                .roles_mut()
+                .into_iter()
+                .find(|(known, _)| known == role)
+                .map(|(_, slot)| slot)
+                .ok_or_else(|| eyre!("unknown role '{}'", role))?;
+            *slot = colour;
+        }
+
+        Ok(theme)
+    }
+
+    fn roles_mut(&mut self) -> Vec<(&'static str, &mut Color)> {
+        let [scale_good, scale_fair, scale_poor, scale_bad] = &mut self.scale;
+        vec![
+            ("text", &mut self.text),
+            ("background", &mut self.background),
+            ("muted", &mut self.muted),
+            ("border", &mut self.border),
+            ("title", &mut self.title),
+            ("highlight", &mut self.highlight),
+            ("source", &mut self.source),
+            ("url", &mut self.url),
+            ("correct", &mut self.correct),
+            ("incorrect", &mut self.incorrect),
+            ("incorrect_text", &mut self.incorrect_text),
+            ("pending", &mut self.pending),
+            ("cursor", &mut self.cursor),
+            ("cursor_text", &mut self.cursor_text),
+            ("ghost", &mut self.ghost),
+            ("gauge", &mut self.gauge),
+            ("chart", &mut self.chart),
+            ("error", &mut self.error),
+            ("warning", &mut self.warning),
+            ("scale_good", scale_good),
+            ("scale_fair", scale_fair),
+            ("scale_poor", scale_poor),
+            ("scale_bad", scale_bad),
+            ("no_data", &mut self.no_data),
+            ("key_text", &mut self.key_text),
+            ("log_error", &mut self.log_error),
+            ("log_warn", &mut self.log_warn),
+            ("log_info", &mut self.log_info),
+            ("log_debug", &mut self.log_debug),
+            ("log_trace", &mut self.log_trace),
+        ]
+    }
+}
+
+/// Reads a colour name such as `lightcyan` or `dark-gray`, or `#rrggbb`.
+pub fn parse_colour(value: &str) -> Result<Color> {
+    if let Some(hex) = value.strip_prefix('#') {
+        let channel = |range: std::ops::Range<usize>| {
+            hex.get(range)
+                .and_then(|channel| u8::from_str_radix(channel, 16).ok())
+        };
+        return match (hex.len(), channel(0..2), channel(2..4), channel(4..6)) {
+            (6, Some(r), Some(g), Some(b)) => Ok(Color::Rgb(r, g, b)),
+            _ => Err(eyre!("'{}' is not a #rrggbb colour", value)),
+        };
+    }
+
+    let name = value.to_lowercase().replace(['-', '_', ' '], "");
+    let colour = match name.as_str() {
+        "reset" => Color::Reset,
+        "black" => Color::Black,
+        "red" => Color::Red,
+        "green" => Color::Green,
+        "yellow" => Color::Yellow,
+        "blue" => Color::Blue,
+        "magenta" => Color::Magenta,
+        "cyan" => Color::Cyan,
+        "gray" | "grey" => Color::Gray,
+        "darkgray" | "darkgrey" => Color::DarkGray,
+        "lightred" => Color::LightRed,
+        "lightgreen" => Color::LightGreen,
+        "lightyellow" => Color::LightYellow,
+        "lightblue" => Color::LightBlue,
+        "lightmagenta" => Color::LightMagenta,
+        "lightcyan" => Color::LightCyan,
+        "white" => Color::White,
+        _ => return Err(eyre!("unknown colour '{}'", value)),
+    };
+
+    Ok(colour)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn should_ship_distinct_built_in_themes() {
+        let themes = Theme::built_in();
+        assert_eq!(themes[0], Theme::default());
+        for (index, theme) in themes.iter().enumerate() {
+            assert!(themes[index + 1..]
+                .iter()
+                .all(|other| other.name != theme.name));
+            assert_ne!(theme.correct, theme.incorrect);
+        }
+    }
+
+    #[test]
+    fn should_parse_colours() {
+        assert_eq!(parse_colour("LightCyan").unwrap(), Color::LightCyan);
+        assert_eq!(parse_colour("dark-grey").unwrap(), Color::DarkGray);
+        assert_eq!(parse_colour("#0072b2").unwrap(), Color::Rgb(0, 114, 178));
+        assert!(parse_colour("#0072b").is_err());
+        assert!(parse_colour("#zz72b2").is_err());
+        assert!(parse_colour("mauve").is_err());
+    }
+
+    #[test]
+    fn should_load_theme_from_toml() {
+        let theme = Theme::from_toml(
+            "ocean",
+            r##"
+base = "high-contrast"
+correct = "#00ff00"
+scale_bad = "magenta"
+"##,
+        )
+        .unwrap();
+
+        assert_eq!(theme.name, "ocean");
+        assert_eq!(theme.correct, Color::Rgb(0, 255, 0));
+        assert_eq!(theme.scale[3], Color::Magenta);
+        assert_eq!(theme.cursor, Theme::high_contrast().cursor);
+    }
+
+    #[test]
+    fn should_reject_invalid_themes() {
+        let error = |content| Theme::from_toml("bad", content).unwrap_err().to_string();
+
+        assert_eq!(error("cursor = \"mauve\""), "invalid cursor");
+        assert_eq!(error("sparkles = \"red\""), "unknown role 'sparkles'");
+        assert_eq!(error("base = \"neon\""), "unknown base theme 'neon'");
+        assert!(Theme::from_toml("bad", "correct = 3").is_err());
+    }
+}
@@ -1,50 +1,119 @@
-use std::time::Duration;
+use std::time::Instant;
 
-use log::info;
+use chrono::Local;
 use symbols::line;
 use tui::backend::Backend;
 use tui::layout::{Alignment, Constraint, Direction, Layout, Rect};
-use tui::style::{Color, Modifier, Style};
+use tui::style::{Modifier, Style};
 use tui::text::{Span, Spans, Text};
-use tui::widgets::{Block, BorderType, Borders, LineGauge, Paragraph};
+use tui::widgets::{
+    Axis, Block, BorderType, Borders, Chart, Dataset, GraphType, LineGauge, List, ListItem,
+    Paragraph, Sparkline, Wrap,
+};
 use tui::{symbols, Frame};
 use tui_logger::TuiLoggerWidget;
+use unicode_width::UnicodeWidthChar;
 
+use super::actions::{Action, KeyBindings};
+use super::heatmap::{Heatmap, HeatmapSource};
+use super::history::HistoryView;
+use super::menu::{Menu, MenuItem};
+use super::settings::{SettingsItem, SettingsView};
 use super::state::AppState;
+use super::theme::Theme;
 use crate::app::App;
-use crate::io::file::TypingFileDisplay;
+use crate::engine::mode::TestMode;
+use crate::engine::score::{EndReason, TestResult};
+use crate::engine::stats::{KeyStat, TypingStats};
+use crate::io::file::{TextError, TypingFileDisplay};
+
+/// Layout picked from the size of the terminal. Smaller screens leave out
+/// the log panel, then everything but the text and the stats.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Screen {
+    Full,
+    Compact,
+    Minimal,
+    TooSmall,
+}
+
+impl Screen {
+    const FULL: (u16, u16) = (52, 28);
+    const COMPACT: (u16, u16) = (40, 20);
+    const MINIMAL: (u16, u16) = (20, 6);
+
+    fn from_size(size: Rect) -> Self {
+        let fits = |(width, height): (u16, u16)| size.width >= width && size.height >= height;
+        if fits(Self::FULL) {
+            Screen::Full
+        } else if fits(Self::COMPACT) {
+            Screen::Compact
+        } else if fits(Self::MINIMAL) {
+            Screen::Minimal
+        } else {
+            Screen::TooSmall
+        }
+    }
+}
 
 pub fn draw<B>(rect: &mut Frame<B>, app: &App)
 where
     B: Backend,
 {
-    let size = rect.size();
-    check_size(&size);
+    let screen = Screen::from_size(rect.size());
+    let theme = app.theme();
+    let keys = app.key_bindings();
 
-    let chunks = Layout::default()
-        .direction(Direction::Vertical)
-        .constraints(
-            [
-                Constraint::Length(3),
-                Constraint::Length(15),
-                Constraint::Length(3),
-                Constraint::Length(3),
-                Constraint::Max(5),
-            ]
-            .as_ref(),
-        )
-        .split(size);
+    if screen == Screen::TooSmall {
+        draw_too_small(rect, theme);
+        return;
+    }
 
-    let title = draw_title();
-    rect.render_widget(title, chunks[0]);
+    if let Some(error) = app.state().error() {
+        draw_error_screen(rect, screen, error, keys, theme);
+        return;
+    }
 
-    let body_chunks = Layout::default()
-        .direction(Direction::Vertical)
-        .constraints([Constraint::Length(5), Constraint::Length(5)].as_ref())
-        .split(chunks[1]);
+    if app.state().is_menu() {
+        draw_menu_screen(rect, screen, app.menu(), keys, theme);
+        return;
+    }
+
+    if let Some(view) = app.state().history() {
+        draw_history_screen(rect, screen, view, keys, theme);
+        return;
+    }
+
+    if let Some(view) = app.state().settings() {
+        draw_settings_screen(rect, screen, view, keys, theme);
+        return;
+    }
+
+    if screen == Screen::Minimal {
+        draw_minimal_test_screen(rect, app.state(), app.now(), keys, theme);
+        return;
+    }
+
+    // The results leave the rest of the screen to the heatmap, while the
+    // text takes what the live panel does not need.
+    let body = if app.state().result().is_some() {
+        [
+            Constraint::Length(4),
+            Constraint::Length(10),
+            Constraint::Min(0),
+        ]
+    } else {
+        [
+            Constraint::Length(4),
+            Constraint::Min(5),
+            Constraint::Length(5),
+        ]
+    };
+    let chunks = split_screen(rect, screen, &body, theme);
 
     let typing_information = if let Some(typing_information) = app.state().typing_information() {
         TypingFileDisplay {
+            id: typing_information.id,
             from: format!("From: {}", typing_information.from),
             url: format!("Url: {}", typing_information.url),
             content: typing_information.content,
@@ -52,6 +121,7 @@ where
         }
     } else {
         TypingFileDisplay {
+            id: "".to_owned(),
             from: "".to_owned(),
             content: "".to_owned(),
             url: "".to_owned(),
@@ -59,142 +129,1290 @@ where
         }
     };
 
-    let body = draw_typing_information(typing_information.from, typing_information.url);
-    rect.render_widget(body, body_chunks[0]);
+    let body = draw_typing_information(typing_information.from, typing_information.url, theme);
+    rect.render_widget(body, chunks[0]);
 
-    let long_text = draw_typing_text(typing_information.content, app.state());
-    rect.render_widget(long_text, body_chunks[1]);
+    if let Some(result) = app.state().result() {
+        // Built from every keystroke, so only once per frame.
+        let session_stats = app.state().typing_stats().unwrap_or_default();
+        let result_block = draw_result(result, &session_stats, keys, theme);
+        rect.render_widget(result_block, chunks[1]);
 
-    let typing_from_user = draw_typing_from_user(app.state());
-    rect.render_widget(typing_from_user, chunks[2]);
+        let settings = app.heatmap();
+        let stats = match settings.source {
+            HeatmapSource::Session => &session_stats,
+            HeatmapSource::History => app.overall_stats(),
+        };
+        let heatmap = Heatmap::new(stats)
+            .layout(app.keyboard_layout())
+            .metric(settings.metric)
+            .theme(theme)
+            .block(
+                Block::default()
+                    .title(format!(
+                        "{} - {} switch",
+                        settings.title(),
+                        keys.label(Action::SwitchHeatmap)
+                    ))
+                    .border_style(Style::default().fg(theme.border))
+                    .borders(Borders::ALL),
+            );
+        rect.render_widget(heatmap, chunks[2]);
+    } else {
+        let to_type = app.state().to_type().unwrap_or_default().to_owned();
+        let long_text = draw_typing_text(to_type, app.state(), app.now(), chunks[1], theme);
+        rect.render_widget(long_text, chunks[1]);
 
-    if let Some(duration) = app.state().duration() {
-        let duration_block = draw_duration(duration);
-        rect.render_widget(duration_block, chunks[3]);
+        let live_chunks = Layout::default()
+            .direction(Direction::Horizontal)
+            .constraints([Constraint::Percentage(40), Constraint::Percentage(60)].as_ref())
+            .split(chunks[2]);
+        let timer_chunks = Layout::default()
+            .direction(Direction::Vertical)
+            .constraints([Constraint::Length(3), Constraint::Min(1)].as_ref())
+            .split(live_chunks[1]);
+
+        if let Some(live) = draw_live_stats(app.state(), app.now(), theme) {
+            rect.render_widget(live, live_chunks[0]);
+        }
+        if let Some(duration_block) = draw_duration(app.state(), app.now(), keys, theme) {
+            rect.render_widget(duration_block, timer_chunks[0]);
+        }
+        rect.render_widget(
+            draw_wpm_sparkline(app.state(), timer_chunks[1], theme),
+            timer_chunks[1],
+        );
     }
+}
 
-    let logs = draw_logs();
-    rect.render_widget(logs, chunks[4]);
+/// Draws the title and the logs around the body when the screen has room for
+/// them, and returns the areas of the body rows.
+fn split_screen<B>(
+    rect: &mut Frame<B>,
+    screen: Screen,
+    body: &[Constraint],
+    theme: &Theme,
+) -> Vec<Rect>
+where
+    B: Backend,
+{
+    let with_title = screen != Screen::Minimal;
+    let with_logs = screen == Screen::Full;
+
+    let mut constraints = vec![];
+    if with_title {
+        constraints.push(Constraint::Length(3));
+    }
+    constraints.extend_from_slice(body);
+    if with_logs {
+        constraints.push(Constraint::Length(5));
+    }
+
+    let mut chunks = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints(constraints)
+        .split(rect.size());
+
+    if with_logs {
+        let logs = chunks.pop().unwrap();
+        rect.render_widget(draw_logs(theme), logs);
+    }
+    if with_title {
+        let title = chunks.remove(0);
+        rect.render_widget(draw_title(theme), title);
+    }
+
+    chunks
+}
+
+fn draw_too_small<B>(rect: &mut Frame<B>, theme: &Theme)
+where
+    B: Backend,
+{
+    let size = rect.size();
+    let (width, height) = Screen::MINIMAL;
+    let message = Paragraph::new(vec![
+        Spans::from(Span::styled(
+            "Please enlarge the terminal",
+            Style::default().fg(theme.warning),
+        )),
+        Spans::from(format!(
+            "{}x{}, need {}x{}",
+            size.width, size.height, width, height
+        )),
+    ])
+    .alignment(Alignment::Center)
+    .wrap(Wrap { trim: true });
+
+    rect.render_widget(message, size);
 }
 
-fn draw_title<'a>() -> Paragraph<'a> {
+/// Only the text, or the result, with the stats on a single line.
+fn draw_minimal_test_screen<B>(
+    rect: &mut Frame<B>,
+    state: &AppState,
+    now: Instant,
+    keys: &KeyBindings,
+    theme: &Theme,
+) where
+    B: Backend,
+{
+    if let Some(result) = state.result() {
+        let stats = state.typing_stats().unwrap_or_default();
+        rect.render_widget(draw_result(result, &stats, keys, theme), rect.size());
+        return;
+    }
+
+    let chunks = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([Constraint::Min(3), Constraint::Length(1)].as_ref())
+        .split(rect.size());
+
+    let to_type = state.to_type().unwrap_or_default().to_owned();
+    rect.render_widget(
+        draw_typing_text(to_type, state, now, chunks[0], theme),
+        chunks[0],
+    );
+    if let Some(status) = draw_status_line(state, now, theme) {
+        rect.render_widget(status, chunks[1]);
+    }
+}
+
+fn draw_status_line<'a>(state: &AppState, now: Instant, theme: &Theme) -> Option<Paragraph<'a>> {
+    let score = state.score(now)?;
+    let (typed_words, total_words) = state.words_progress()?;
+    let time = match state.remaining(now) {
+        Some(remaining) => format!("{}s left", remaining.as_secs()),
+        None => format!("{}s", state.elapsed(now)?.as_secs()),
+    };
+    let paused = if state.is_paused() { " (paused)" } else { "" };
+
+    Some(
+        Paragraph::new(format!(
+            "{:.0} WPM {:.0}% {}/{}w {}{}",
+            score.net_wpm, score.accuracy.percentage, typed_words, total_words, time, paused
+        ))
+        .style(Style::default().fg(theme.highlight)),
+    )
+}
+
+fn draw_title<'a>(theme: &Theme) -> Paragraph<'a> {
     Paragraph::new("Jackdull")
-        .style(Style::default().fg(Color::LightCyan))
+        .style(Style::default().fg(theme.title))
         .alignment(Alignment::Center)
         .block(
             Block::default()
                 .borders(Borders::ALL)
-                .style(Style::default().fg(Color::White))
+                .style(Style::default().fg(theme.border))
                 .border_type(BorderType::Plain),
         )
 }
 
-fn check_size(rect: &Rect) {
-    if rect.width < 52 {
-        panic!("Require width >= 52, (got {})", rect.width);
+fn draw_menu_screen<B>(
+    rect: &mut Frame<B>,
+    screen: Screen,
+    menu: &Menu,
+    keys: &KeyBindings,
+    theme: &Theme,
+) where
+    B: Backend,
+{
+    if screen == Screen::Minimal {
+        rect.render_widget(draw_menu(menu, theme), rect.size());
+        return;
     }
-    if rect.height < 28 {
-        panic!("Require height >= 28, (got {})", rect.height);
+
+    let chunks = split_screen(
+        rect,
+        screen,
+        &[Constraint::Min(8), Constraint::Length(3)],
+        theme,
+    );
+    rect.render_widget(draw_menu(menu, theme), chunks[0]);
+    rect.render_widget(
+        draw_help(
+            key_help(
+                keys,
+                &[
+                    (&[Action::Up, Action::Down], "choose"),
+                    (&[Action::Left, Action::Right], "change"),
+                    (&[Action::Select], "select"),
+                    (&[Action::Quit], "quit"),
+                ],
+            ),
+            theme,
+        ),
+        chunks[1],
+    );
+}
+
+fn draw_menu<'a>(menu: &Menu, theme: &Theme) -> Paragraph<'a> {
+    let lines = MenuItem::ALL
+        .iter()
+        .map(|item| {
+            let is_selected = *item == menu.selected();
+            let marker = if is_selected { "> " } else { "  " };
+            let value = menu.value(*item);
+            let text = if value.is_empty() {
+                format!("{}{}", marker, item.name())
+            } else {
+                format!("{}{:<8} < {} >", marker, item.name(), value)
+            };
+            let style = if is_selected {
+                Style::default()
+                    .fg(theme.highlight)
+                    .add_modifier(Modifier::BOLD)
+            } else {
+                Style::default().fg(theme.text)
+            };
+            Spans::from(Span::styled(text, style))
+        })
+        .collect::<Vec<_>>();
+
+    Paragraph::new(lines).alignment(Alignment::Left).block(
+        Block::default()
+            .title("Menu")
+            .border_style(Style::default().fg(theme.border))
+            .borders(Borders::ALL),
+    )
+}
+
+fn draw_history_screen<B>(
+    rect: &mut Frame<B>,
+    screen: Screen,
+    view: &HistoryView,
+    keys: &KeyBindings,
+    theme: &Theme,
+) where
+    B: Backend,
+{
+    if screen == Screen::Minimal {
+        rect.render_widget(draw_history_summary(view, theme), rect.size());
+        return;
+    }
+
+    // The log panel is left out to give the charts more room, and so are the
+    // recent sessions on compact screens.
+    let chunks = match screen {
+        Screen::Full => split_screen(
+            rect,
+            Screen::Compact,
+            &[
+                Constraint::Length(8),
+                Constraint::Min(8),
+                Constraint::Length(7),
+                Constraint::Length(3),
+            ],
+            theme,
+        ),
+        _ => split_screen(
+            rect,
+            screen,
+            &[
+                Constraint::Length(8),
+                Constraint::Min(6),
+                Constraint::Length(0),
+                Constraint::Length(3),
+            ],
+            theme,
+        ),
+    };
+
+    let summary_chunks = Layout::default()
+        .direction(Direction::Horizontal)
+        .constraints([Constraint::Percentage(50), Constraint::Percentage(50)].as_ref())
+        .split(chunks[0]);
+
+    let chart_chunks = Layout::default()
+        .direction(Direction::Horizontal)
+        .constraints([Constraint::Percentage(60), Constraint::Percentage(40)].as_ref())
+        .split(chunks[1]);
+
+    rect.render_widget(draw_history_summary(view, theme), summary_chunks[0]);
+    rect.render_widget(draw_personal_bests(view, theme), summary_chunks[1]);
+    let points = wpm_points(view);
+    rect.render_widget(draw_wpm_chart(&points, theme), chart_chunks[0]);
+    let accuracies = accuracy_values(view, chart_chunks[1]);
+    rect.render_widget(draw_accuracy_sparkline(&accuracies, theme), chart_chunks[1]);
+    if chunks[2].height > 0 {
+        rect.render_widget(draw_recent_sessions(view, chunks[2], theme), chunks[2]);
+    }
+    rect.render_widget(
+        draw_help(
+            key_help(
+                keys,
+                &[
+                    (&[Action::Left, Action::Right], "mode"),
+                    (&[Action::Up, Action::Down], "passage"),
+                    (&[Action::Back], "menu"),
+                    (&[Action::Quit], "quit"),
+                ],
+            ),
+            theme,
+        ),
+        chunks[3],
+    );
+}
+
+fn draw_history_summary<'a>(view: &HistoryView, theme: &Theme) -> Paragraph<'a> {
+    let query = view.query();
+    let average = |value: Option<f64>, unit: &str| {
+        value.map_or_else(|| "-".to_owned(), |value| format!("{:.1}{}", value, unit))
+    };
+
+    Paragraph::new(vec![
+        Spans::from(format!("Mode    < {} >", view.mode_label())),
+        Spans::from(format!("Passage < {} >", view.passage_label())),
+        Spans::from(format!("Sessions: {}", query.records().len())),
+        Spans::from(format!(
+            "WPM last 10: {}, last 100: {}",
+            average(query.average_wpm(10), ""),
+            average(query.average_wpm(100), "")
+        )),
+        Spans::from(format!(
+            "Acc last 10: {}, last 100: {}",
+            average(query.average_accuracy(10), "%"),
+            average(query.average_accuracy(100), "%")
+        )),
+    ])
+    .style(Style::default().fg(theme.text))
+    .block(
+        Block::default()
+            .title("History")
+            .border_style(Style::default().fg(theme.border))
+            .borders(Borders::ALL),
+    )
+}
+
+fn draw_personal_bests<'a>(view: &HistoryView, theme: &Theme) -> Paragraph<'a> {
+    let lines = view
+        .personal_bests()
+        .into_iter()
+        .map(|record| {
+            Spans::from(vec![
+                Span::styled(
+                    format!("{:<8}", record.mode.to_string()),
+                    Style::default().fg(theme.highlight),
+                ),
+                Span::raw(format!("{:.0} WPM, {:.1}%", record.wpm, record.accuracy)),
+            ])
+        })
+        .collect::<Vec<_>>();
+
+    Paragraph::new(lines)
+        .style(Style::default().fg(theme.text))
+        .block(
+            Block::default()
+                .title("Personal bests")
+                .border_style(Style::default().fg(theme.border))
+                .borders(Borders::ALL),
+        )
+}
+
+/// WPM of each session, numbered from the oldest one.
+fn wpm_points(view: &HistoryView) -> Vec<(f64, f64)> {
+    view.query()
+        .records()
+        .iter()
+        .enumerate()
+        .map(|(index, record)| ((index + 1) as f64, record.wpm))
+        .collect()
+}
+
+fn draw_wpm_chart<'a>(points: &'a [(f64, f64)], theme: &Theme) -> Chart<'a> {
+    let max_x = (points.len() as f64).max(2.0);
+    let max_wpm = points.iter().map(|(_, wpm)| *wpm).fold(0.0, f64::max);
+    let max_y = ((max_wpm / 10.0).ceil() * 10.0).max(10.0);
+
+    let dataset = Dataset::default()
+        .name("WPM")
+        .marker(symbols::Marker::Braille)
+        .graph_type(GraphType::Line)
+        .style(Style::default().fg(theme.chart))
+        .data(points);
+
+    Chart::new(vec![dataset])
+        .block(
+            Block::default()
+                .title("WPM over time")
+                .border_style(Style::default().fg(theme.border))
+                .borders(Borders::ALL),
+        )
+        .x_axis(
+            Axis::default()
+                .style(Style::default().fg(theme.muted))
+                .bounds([1.0, max_x])
+                .labels(vec![
+                    Span::raw("1"),
+                    Span::raw(format!("{}", points.len().max(1))),
+                ]),
+        )
+        .y_axis(
+            Axis::default()
+                .style(Style::default().fg(theme.muted))
+                .bounds([0.0, max_y])
+                .labels(vec![
+                    Span::raw("0"),
+                    Span::raw(format!("{:.0}", max_y / 2.0)),
+                    Span::raw(format!("{:.0}", max_y)),
+                ]),
+        )
+}
+
+/// Accuracy of the most recent sessions, as many as fit in the area.
+fn accuracy_values(view: &HistoryView, area: Rect) -> Vec<u64> {
+    let width = area.width.saturating_sub(2) as usize;
+    view.query()
+        .last(width)
+        .iter()
+        .map(|record| record.accuracy.round() as u64)
+        .collect()
+}
+
+fn draw_accuracy_sparkline<'a>(accuracies: &'a [u64], theme: &Theme) -> Sparkline<'a> {
+    Sparkline::default()
+        .block(
+            Block::default()
+                .title("Accuracy")
+                .border_style(Style::default().fg(theme.border))
+                .borders(Borders::ALL),
+        )
+        .style(Style::default().fg(theme.correct))
+        .max(100)
+        .data(accuracies)
+}
+
+fn draw_recent_sessions<'a>(view: &HistoryView, area: Rect, theme: &Theme) -> List<'a> {
+    let count = area.height.saturating_sub(2) as usize;
+    let items = view
+        .query()
+        .last(count)
+        .into_iter()
+        .rev()
+        .map(|record| {
+            ListItem::new(format!(
+                "{}  {:<7}  #{:<4}  {:>3.0} WPM  {:>5.1}%",
+                record
+                    .timestamp
+                    .with_timezone(&Local)
+                    .format("%Y-%m-%d %H:%M"),
+                record.mode.to_string(),
+                record.passage_id,
+                record.wpm,
+                record.accuracy
+            ))
+        })
+        .collect::<Vec<_>>();
+
+    List::new(items)
+        .style(Style::default().fg(theme.text))
+        .block(
+            Block::default()
+                .title("Recent sessions")
+                .border_style(Style::default().fg(theme.border))
+                .borders(Borders::ALL),
+        )
+}
+
+fn draw_settings_screen<B>(
+    rect: &mut Frame<B>,
+    screen: Screen,
+    view: &SettingsView,
+    keys: &KeyBindings,
+    theme: &Theme,
+) where
+    B: Backend,
+{
+    if screen == Screen::Minimal {
+        rect.render_widget(draw_settings(view, theme), rect.size());
+        return;
     }
+
+    let chunks = split_screen(
+        rect,
+        screen,
+        &[Constraint::Min(9), Constraint::Length(3)],
+        theme,
+    );
+    rect.render_widget(draw_settings(view, theme), chunks[0]);
+    rect.render_widget(
+        draw_help(
+            key_help(
+                keys,
+                &[
+                    (&[Action::Up, Action::Down], "choose"),
+                    (&[Action::Left, Action::Right], "change"),
+                    (&[Action::Select], "save"),
+                    (&[Action::Back], "cancel"),
+                ],
+            ),
+            theme,
+        ),
+        chunks[1],
+    );
+}
+
+fn draw_settings<'a>(view: &SettingsView, theme: &Theme) -> Paragraph<'a> {
+    let mut lines = SettingsItem::ALL
+        .iter()
+        .map(|item| {
+            let is_selected = *item == view.selected();
+            let marker = if is_selected { "> " } else { "  " };
+            let value = view.value(*item);
+            let text = if value.is_empty() {
+                format!("{}{}", marker, item.name())
+            } else {
+                format!("{}{:<9} < {} >", marker, item.name(), value)
+            };
+            let style = if is_selected {
+                Style::default()
+                    .fg(theme.highlight)
+                    .add_modifier(Modifier::BOLD)
+            } else {
+                Style::default().fg(theme.text)
+            };
+            Spans::from(Span::styled(text, style))
+        })
+        .collect::<Vec<_>>();
+    lines.push(Spans::from(""));
+    lines.push(Spans::from(Span::styled(
+        format!("  Texts     {}", view.config().texts_dir.display()),
+        Style::default().fg(theme.muted),
+    )));
+    lines.push(Spans::from(Span::styled(
+        "  Keys and texts are set in config.toml, and read on start",
+        Style::default().fg(theme.muted),
+    )));
+
+    Paragraph::new(lines)
+        .alignment(Alignment::Left)
+        .wrap(Wrap { trim: false })
+        .block(
+            Block::default()
+                .title("Settings")
+                .border_style(Style::default().fg(theme.border))
+                .borders(Borders::ALL),
+        )
+}
+
+/// Lists the keys bound to each group of actions, such as
+/// `<Up/Down> choose, <Ctrl+c> quit`.
+fn key_help(keys: &KeyBindings, entries: &[(&[Action], &str)]) -> String {
+    entries
+        .iter()
+        .map(|(actions, what)| {
+            let labels = actions
+                .iter()
+                .map(|action| {
+                    let label = keys.label(*action);
+                    label
+                        .trim_start_matches('<')
+                        .trim_end_matches('>')
+                        .to_owned()
+                })
+                .collect::<Vec<_>>();
+            format!("<{}> {}", labels.join("/"), what)
+        })
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+fn draw_help<'a>(help: String, theme: &Theme) -> Paragraph<'a> {
+    Paragraph::new(help)
+        .style(Style::default().fg(theme.muted))
+        .alignment(Alignment::Center)
+        .block(
+            Block::default()
+                .border_style(Style::default().fg(theme.muted))
+                .borders(Borders::ALL),
+        )
+}
+
+fn draw_error_screen<B>(
+    rect: &mut Frame<B>,
+    screen: Screen,
+    error: &TextError,
+    keys: &KeyBindings,
+    theme: &Theme,
+) where
+    B: Backend,
+{
+    let chunks = split_screen(rect, screen, &[Constraint::Min(5)], theme);
+    rect.render_widget(draw_error(error, keys, theme), chunks[0]);
+}
+
+fn draw_error<'a>(error: &TextError, keys: &KeyBindings, theme: &Theme) -> Paragraph<'a> {
+    Paragraph::new(vec![
+        Spans::from(Span::styled(
+            "Cannot load the text to type",
+            Style::default()
+                .fg(theme.error)
+                .add_modifier(Modifier::BOLD),
+        )),
+        Spans::from(""),
+        Spans::from(error.to_string()),
+        Spans::from(""),
+        Spans::from(Span::styled(
+            format!("Press {} to quit", keys.label(Action::Quit)),
+            Style::default().fg(theme.muted),
+        )),
+    ])
+    .style(Style::default().fg(theme.text))
+    .alignment(Alignment::Center)
+    .wrap(Wrap { trim: true })
+    .block(
+        Block::default()
+            .title("Error")
+            .border_style(Style::default().fg(theme.error))
+            .borders(Borders::ALL),
+    )
 }
 
 fn draw_typing_information<'a>(
     typing_information_from: String,
     typing_information_url: String,
+    theme: &Theme,
 ) -> Paragraph<'a> {
     Paragraph::new(vec![
         Spans::from(Span::styled(
             typing_information_from,
             Style::default()
-                .fg(Color::Yellow)
+                .fg(theme.source)
                 .add_modifier(Modifier::ITALIC),
         )),
         Spans::from(Span::styled(
             typing_information_url,
             Style::default()
-                .fg(Color::Blue)
+                .fg(theme.url)
                 .add_modifier(Modifier::ITALIC),
         )),
     ])
-    .style(Style::default().fg(Color::LightCyan))
+    .style(Style::default().fg(theme.title))
     .alignment(Alignment::Center)
     .block(
         Block::default()
-            .border_style(Style::default().fg(Color::DarkGray))
+            .border_style(Style::default().fg(theme.muted))
             .borders(Borders::ALL),
     )
 }
 
-fn draw_typing_text<'a>(text: String, state: &AppState) -> Paragraph<'a> {
-    let long_text = Text::from(text);
+/// The target text with the typed characters overlaid in place, wrapped to
+/// the width of the area and scrolled to keep the cursor line visible. The
+/// cursor of the ghost, if any, is drawn as well, and the title tells how far
+/// ahead of it the player is.
+fn draw_typing_text<'a>(
+    text: String,
+    state: &AppState,
+    now: Instant,
+    area: Rect,
+    theme: &Theme,
+) -> Paragraph<'a> {
+    let typed_text = state.typed_text().unwrap_or_default();
+    let width = area.width.saturating_sub(2) as usize;
+    let height = area.height.saturating_sub(2) as usize;
+    let ghost = state.ghost_position(now);
+    let (lines, cursor_line) = typing_lines(&text, &typed_text, ghost, width);
 
-    let typed_text = state.typed_text();
-    if let Some(typed) = typed_text {
-        info!("typed: {}", typed);
-    }
+    // Keep one line of context above the cursor, as long as the end of the
+    // text is not reached.
+    let scroll = cursor_line
+        .saturating_sub(1)
+        .min(lines.len().saturating_sub(height));
+    let long_text = Text::from(
+        lines
+            .into_iter()
+            .map(|line| group_spans(line, theme))
+            .collect::<Vec<_>>(),
+    );
+    let title = match (state.playback(), state.ghost(), state.ghost_lead(now)) {
+        (Some(playback), _, _) => Span::styled(
+            format!("Replay x{}", playback.speed()),
+            Style::default().fg(theme.highlight),
+        ),
+        (None, Some(ghost), Some(lead)) => Span::styled(
+            format!(
+                "{:+} chars ahead of {} run ({:.0} WPM)",
+                lead, ghost.run, ghost.wpm
+            ),
+            Style::default().fg(if lead < 0 {
+                theme.warning
+            } else {
+                theme.highlight
+            }),
+        ),
+        _ => Span::raw(""),
+    };
 
     Paragraph::new(long_text)
-        .style(Style::default().fg(Color::White))
+        .style(Style::default().fg(theme.text))
         .alignment(Alignment::Left)
+        .scroll((scroll as u16, 0))
         .block(
             Block::default()
-                .border_style(Style::default().fg(Color::White).bg(Color::Black))
+                .title(title)
+                .border_style(Style::default().fg(theme.border).bg(theme.background))
                 .borders(Borders::ALL),
         )
 }
 
-fn draw_typing_from_user<'a>(state: &AppState) -> Paragraph<'a> {
-    let typing = if let Some(typed_text) = state.typed_text() {
-        format!("{}", typed_text)
-    } else {
-        String::default()
+fn draw_result<'a>(
+    result: &TestResult,
+    stats: &TypingStats,
+    keys: &KeyBindings,
+    theme: &Theme,
+) -> Paragraph<'a> {
+    let title = match result.reason {
+        EndReason::Finished => "Finished!",
+        EndReason::TimeUp => "Time is up!",
     };
+    let score = &result.score;
 
-    Paragraph::new(typing)
-        .style(
+    Paragraph::new(vec![
+        Spans::from(Span::styled(
+            title,
             Style::default()
-                .fg(Color::Black)
-                .bg(Color::Cyan)
+                .fg(theme.highlight)
                 .add_modifier(Modifier::BOLD),
-        )
-        .alignment(Alignment::Left)
+        )),
+        Spans::from(format!(
+            "{:.0} WPM (raw {:.0}) - {:.0} CPM in {:.3}s",
+            score.net_wpm,
+            score.gross_wpm,
+            score.cpm,
+            score.elapsed.as_secs_f64()
+        )),
+        Spans::from(format!(
+            "Accuracy: {:.1}% ({}/{} keystrokes)",
+            score.accuracy.percentage,
+            score.accuracy.correct_keystrokes,
+            score.accuracy.total_keystrokes
+        )),
+        Spans::from(format!(
+            "Errors: {} corrected, {} uncorrected",
+            score.accuracy.corrected_errors, score.accuracy.uncorrected_errors
+        )),
+        Spans::from(format!(
+            "Slowest keys: {}",
+            latencies(
+                stats
+                    .slowest_keys(5)
+                    .into_iter()
+                    .map(|(key, stat)| { (visible_glyph(key).to_string(), stat) })
+            )
+        )),
+        Spans::from(format!(
+            "Slowest bigrams: {}",
+            latencies(
+                stats
+                    .slowest_bigrams(5)
+                    .into_iter()
+                    .map(|(bigram, stat)| (bigram.chars().map(visible_glyph).collect(), stat))
+            )
+        )),
+        Spans::from(format!(
+            "Most missed: {}",
+            stats
+                .most_missed_keys(5)
+                .into_iter()
+                .map(|(key, stat)| format!(
+                    "{} {:.0}%",
+                    visible_glyph(key),
+                    stat.error_rate() * 100.0
+                ))
+                .collect::<Vec<_>>()
+                .join(", ")
+        )),
+        Spans::from(Span::styled(
+            key_help(
+                keys,
+                &[
+                    (&[Action::Retry], "retry"),
+                    (&[Action::NextText], "next text"),
+                    (&[Action::Select, Action::Back], "menu"),
+                ],
+            ),
+            Style::default().fg(theme.muted),
+        )),
+    ])
+    .style(Style::default().fg(theme.text))
+    .alignment(Alignment::Center)
+    .block(
+        Block::default()
+            .title("Result")
+            .border_style(Style::default().fg(theme.border).bg(theme.background))
+            .borders(Borders::ALL),
+    )
+}
+
+fn latencies(stats: impl Iterator<Item = (String, KeyStat)>) -> String {
+    stats
+        .filter_map(|(name, stat)| {
+            stat.mean_latency()
+                .map(|latency| format!("{} {}ms", name, latency.as_millis()))
+        })
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum CharStatus {
+    Correct,
+    Wrong,
+    Cursor,
+    Ghost,
+    Pending,
+}
+
+impl CharStatus {
+    fn style(&self, theme: &Theme) -> Style {
+        match self {
+            CharStatus::Correct => Style::default().fg(theme.correct),
+            CharStatus::Wrong => Style::default()
+                .fg(theme.incorrect_text)
+                .bg(theme.incorrect)
+                .add_modifier(Modifier::BOLD),
+            CharStatus::Cursor => Style::default()
+                .fg(theme.cursor_text)
+                .bg(theme.cursor)
+                .add_modifier(Modifier::UNDERLINED),
+            CharStatus::Ghost => Style::default().fg(theme.cursor_text).bg(theme.ghost),
+            CharStatus::Pending => Style::default().fg(theme.pending),
+        }
+    }
+}
+
+/// Compares the typed text with the target one character at a time. Wrong
+/// characters show the glyph that was actually typed, so the player can see
+/// what went wrong. The character at `ghost` shows the cursor of the ghost,
+/// unless the cursor of the player is on it. Lines are wrapped between words
+/// to fit `width`, and the index of the line holding the cursor is returned
+/// along with them.
+fn typing_lines(
+    target: &str,
+    typed: &str,
+    ghost: Option<usize>,
+    width: usize,
+) -> (Vec<Vec<StyledChar>>, usize) {
+    let mut typed_chars = typed.chars();
+    let mut cursor_drawn = false;
+    let mut lines: Vec<Vec<(StyledChar, char)>> = vec![vec![]];
+
+    for (index, expected) in target.chars().enumerate() {
+        let (status, glyph) = match typed_chars.next() {
+            Some(c) if c == expected => (CharStatus::Correct, expected),
+            Some(c) => (CharStatus::Wrong, visible_glyph(c)),
+            None if !cursor_drawn => {
+                cursor_drawn = true;
+                (CharStatus::Cursor, visible_glyph(expected))
+            }
+            None => (CharStatus::Pending, expected),
+        };
+        let (status, glyph) = match status {
+            CharStatus::Cursor => (status, glyph),
+            _ if ghost == Some(index) => (CharStatus::Ghost, visible_glyph(glyph)),
+            _ => (status, glyph),
+        };
+
+        if expected == '\n' {
+            if status != CharStatus::Correct && status != CharStatus::Pending {
+                lines.last_mut().unwrap().push(((status, glyph), expected));
+            }
+            lines.push(vec![]);
+        } else {
+            lines.last_mut().unwrap().push(((status, glyph), expected));
+        }
+    }
+
+    for extra in typed_chars {
+        lines
+            .last_mut()
+            .unwrap()
+            .push(((CharStatus::Wrong, visible_glyph(extra)), extra));
+    }
+
+    let lines = lines
+        .into_iter()
+        .flat_map(|line| wrap_line(line, width))
+        .collect::<Vec<_>>();
+    let cursor_line = lines
+        .iter()
+        .position(|line| line.iter().any(|(status, _)| *status == CharStatus::Cursor))
+        .unwrap_or(lines.len().saturating_sub(1));
+
+    (lines, cursor_line)
+}
+
+type StyledChar = (CharStatus, char);
+
+/// Splits a line after the last space that fits in `width` columns, or in
+/// the middle of a word longer than the whole line. Each drawn character
+/// comes with the one of the text it stands for, since a space may be drawn
+/// as '·' and a '·' of the text is no place to split.
+fn wrap_line(mut line: Vec<(StyledChar, char)>, width: usize) -> Vec<Vec<StyledChar>> {
+    if width == 0 {
+        return vec![drawn(line)];
+    }
+    let mut lines = vec![];
+
+    loop {
+        let mut columns = 0;
+        let fitting = line
+            .iter()
+            .take_while(|((_, glyph), _)| {
+                columns += glyph.width().unwrap_or(0);
+                columns <= width
+            })
+            .count();
+        if fitting == line.len() {
+            break;
+        }
+
+        let split = line[..fitting]
+            .iter()
+            .rposition(|(_, source)| source.is_whitespace())
+            .map_or(fitting.max(1), |space| space + 1);
+        let rest = line.split_off(split);
+        lines.push(drawn(line));
+        line = rest;
+    }
+    lines.push(drawn(line));
+
+    lines
+}
+
+fn drawn(line: Vec<(StyledChar, char)>) -> Vec<StyledChar> {
+    line.into_iter().map(|(styled, _)| styled).collect()
+}
+
+/// Spaces and line breaks as drawn on the typing screen.
+pub(crate) fn visible_glyph(c: char) -> char {
+    match c {
+        '\n' => '⏎',
+        ' ' => '·',
+        c => c,
+    }
+}
+
+fn group_spans<'a>(chars: Vec<StyledChar>, theme: &Theme) -> Spans<'a> {
+    let mut spans: Vec<Span> = vec![];
+    let mut current: Option<(CharStatus, String)> = None;
+
+    for (status, glyph) in chars {
+        match current.as_mut() {
+            Some((current_status, content)) if *current_status == status => content.push(glyph),
+            _ => {
+                if let Some((status, content)) = current.take() {
+                    spans.push(Span::styled(content, status.style(theme)));
+                }
+                current = Some((status, glyph.to_string()));
+            }
+        }
+    }
+    if let Some((status, content)) = current {
+        spans.push(Span::styled(content, status.style(theme)));
+    }
+
+    Spans::from(spans)
+}
+
+fn draw_live_stats<'a>(state: &AppState, now: Instant, theme: &Theme) -> Option<Paragraph<'a>> {
+    let score = state.score(now)?;
+    let progress = state.text_progress()?;
+    let (typed_words, total_words) = state.words_progress()?;
+    let errors = score.accuracy.total_keystrokes - score.accuracy.correct_keystrokes;
+
+    Some(
+        Paragraph::new(vec![
+            Spans::from(vec![
+                Span::styled(
+                    format!("{:.0} WPM", score.net_wpm),
+                    Style::default()
+                        .fg(theme.highlight)
+                        .add_modifier(Modifier::BOLD),
+                ),
+                Span::raw(format!(" (raw {:.0})", score.gross_wpm)),
+            ]),
+            Spans::from(format!(
+                "{:.1}%, {} errors",
+                score.accuracy.percentage, errors
+            )),
+            Spans::from(format!(
+                "{}/{} words ({:.0}%)",
+                typed_words,
+                total_words,
+                progress * 100.0
+            )),
+        ])
+        .style(Style::default().fg(theme.text))
         .block(
             Block::default()
-                .border_style(Style::default().fg(Color::White).bg(Color::Cyan))
+                .title("Live")
+                .border_style(Style::default().fg(theme.border).bg(theme.background))
                 .borders(Borders::ALL),
-        )
+        ),
+    )
 }
 
-fn draw_duration(duration: &Duration) -> LineGauge {
-    let sec = duration.as_secs();
-    let label = format!("{}s", sec);
-    let ratio = sec as f64 / 60.0;
+/// Speed of each second of the test, the most recent ones when they do not
+/// all fit.
+fn draw_wpm_sparkline<'a>(state: &'a AppState, area: Rect, theme: &Theme) -> Sparkline<'a> {
+    let samples = state.wpm_samples();
+    let skip = samples.len().saturating_sub(area.width as usize);
 
-    LineGauge::default()
-        .block(Block::default().borders(Borders::ALL).title("Timer"))
-        .gauge_style(
-            Style::default()
-                .fg(Color::Cyan)
-                .bg(Color::Black)
-                .add_modifier(Modifier::BOLD),
+    Sparkline::default()
+        .style(Style::default().fg(theme.chart))
+        .data(&samples[skip..])
+}
+
+fn draw_duration<'a>(
+    state: &AppState,
+    now: Instant,
+    keys: &KeyBindings,
+    theme: &Theme,
+) -> Option<LineGauge<'a>> {
+    let mode = state.mode()?;
+    let elapsed = state.elapsed(now)?;
+
+    let (title, label, ratio) = match mode {
+        TestMode::Timed(limit) => {
+            let remaining = state.remaining(now).unwrap_or_default();
+            let label = format!("{}s ({}s left)", elapsed.as_secs(), remaining.as_secs());
+            let ratio = elapsed.as_secs_f64() / limit.as_secs_f64();
+            ("Timer", label, ratio)
+        }
+        TestMode::Words(goal) => {
+            let (typed, _) = state.words_progress()?;
+            let label = format!("{}/{} words in {}s", typed, goal, elapsed.as_secs());
+            ("Words", label, typed as f64 / goal as f64)
+        }
+        TestMode::Passage => {
+            let (typed, total) = state.words_progress()?;
+            let label = format!("{}/{} words in {}s", typed, total, elapsed.as_secs());
+            ("Progress", label, state.text_progress()?)
+        }
+    };
+    let label = if state.is_paused() {
+        format!(
+            "{} (paused, {} to resume)",
+            label,
+            keys.label(Action::TogglePause)
         )
-        .line_set(line::THICK)
-        .label(label)
-        .ratio(ratio)
+    } else {
+        label
+    };
+
+    Some(
+        LineGauge::default()
+            .block(
+                Block::default()
+                    .title(title)
+                    .border_style(Style::default().fg(theme.border).bg(theme.background))
+                    .borders(Borders::ALL),
+            )
+            .gauge_style(
+                Style::default()
+                    .fg(theme.gauge)
+                    .bg(theme.background)
+                    .add_modifier(Modifier::BOLD),
+            )
+            .line_set(line::THICK)
+            .label(label)
+            .ratio(ratio.min(1.0)),
+    )
 }
 
-fn draw_logs<'a>() -> TuiLoggerWidget<'a> {
+fn draw_logs<'a>(theme: &Theme) -> TuiLoggerWidget<'a> {
     TuiLoggerWidget::default()
-        .style_error(Style::default().fg(Color::Red))
-        .style_debug(Style::default().fg(Color::Green))
-        .style_warn(Style::default().fg(Color::Yellow))
-        .style_trace(Style::default().fg(Color::Gray))
-        .style_info(Style::default().fg(Color::Blue))
+        .style_error(Style::default().fg(theme.log_error))
+        .style_debug(Style::default().fg(theme.log_debug))
+        .style_warn(Style::default().fg(theme.log_warn))
+        .style_trace(Style::default().fg(theme.log_trace))
+        .style_info(Style::default().fg(theme.log_info))
         .block(
             Block::default()
                 .title("Logs")
-                .border_style(Style::default().fg(Color::White).bg(Color::Black))
+                .border_style(Style::default().fg(theme.border).bg(theme.background))
                 .borders(Borders::ALL),
         )
-        .style(Style::default().fg(Color::White).bg(Color::Black))
+        .style(Style::default().fg(theme.text).bg(theme.background))
+}
+
+#[cfg(test)]
+mod tests {
+    use tui::backend::TestBackend;
+    use tui::Terminal;
+
+    use std::time::Duration;
+
+    use tui::style::Color;
+
+    use super::*;
+    use crate::engine::Input;
+
+    fn text(lines: &[Vec<StyledChar>]) -> Vec<String> {
+        lines
+            .iter()
+            .map(|line| line.iter().map(|(_, glyph)| glyph).collect())
+            .collect()
+    }
+
+    #[test]
+    fn should_style_each_character() {
+        let (lines, cursor_line) = typing_lines("abc de", "a c", None, 20);
+
+        assert_eq!(
+            lines[0],
+            vec![
+                (CharStatus::Correct, 'a'),
+                (CharStatus::Wrong, '·'),
+                (CharStatus::Correct, 'c'),
+                (CharStatus::Cursor, '·'),
+                (CharStatus::Pending, 'd'),
+                (CharStatus::Pending, 'e'),
+            ]
+        );
+        assert_eq!(cursor_line, 0);
+
+        let theme = Theme::default();
+        let spans = group_spans(lines[0].clone(), &theme).0;
+        let styled = spans
+            .iter()
+            .map(|span| (span.content.as_ref(), span.style))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            styled,
+            vec![
+                ("a", CharStatus::Correct.style(&theme)),
+                ("·", CharStatus::Wrong.style(&theme)),
+                ("c", CharStatus::Correct.style(&theme)),
+                ("·", CharStatus::Cursor.style(&theme)),
+                ("de", CharStatus::Pending.style(&theme)),
+            ]
+        );
+        assert_eq!(CharStatus::Wrong.style(&theme).bg, Some(theme.incorrect));
+        assert_eq!(CharStatus::Cursor.style(&theme).bg, Some(theme.cursor));
+        assert_eq!(CharStatus::Pending.style(&theme).fg, Some(theme.pending));
+    }
+
+    #[test]
+    fn should_wrap_between_words() {
+        let (lines, cursor_line) = typing_lines("one two three four", "", None, 9);
+
+        assert_eq!(text(&lines), vec!["one two ", "three ", "four"]);
+        assert_eq!(cursor_line, 0);
+    }
+
+    #[test]
+    fn should_split_words_longer_than_a_line() {
+        let (lines, _) = typing_lines("abcdefgh ij", "", None, 4);
+
+        assert_eq!(text(&lines), vec!["abcd", "efgh", " ij"]);
+    }
+
+    #[test]
+    fn should_wrap_on_display_width() {
+        let (lines, _) = typing_lines("日本 語の 本", "", None, 5);
+        assert_eq!(text(&lines), vec!["日本 ", "語の ", "本"]);
+
+        let (lines, _) = typing_lines("ab·cd", "", None, 4);
+        assert_eq!(text(&lines), vec!["ab·c", "d"]);
+    }
+
+    #[test]
+    fn should_follow_the_cursor() {
+        let (lines, cursor_line) = typing_lines("one two\nthree four", "one twx\nthree ", None, 9);
+
+        assert_eq!(text(&lines), vec!["one twx", "three ", "four"]);
+        assert_eq!(lines[0][6], (CharStatus::Wrong, 'x'));
+        assert_eq!(lines[2][0], (CharStatus::Cursor, 'f'));
+        assert_eq!(cursor_line, 2);
+    }
+
+    #[test]
+    fn should_draw_the_ghost_cursor() {
+        let (lines, _) = typing_lines("one two", "onx", Some(5), 9);
+        assert_eq!(lines[0][3], (CharStatus::Cursor, '·'));
+        assert_eq!(lines[0][5], (CharStatus::Ghost, 'w'));
+
+        let (lines, _) = typing_lines("one two", "onx", Some(2), 9);
+        assert_eq!(lines[0][2], (CharStatus::Ghost, 'x'));
+
+        let (lines, _) = typing_lines("one two", "onx", Some(3), 9);
+        assert_eq!(lines[0][3], (CharStatus::Cursor, '·'));
+    }
+
+    #[test]
+    fn should_pick_layout_from_size() {
+        assert_eq!(Screen::from_size(Rect::new(0, 0, 52, 28)), Screen::Full);
+        assert_eq!(Screen::from_size(Rect::new(0, 0, 120, 27)), Screen::Compact);
+        assert_eq!(Screen::from_size(Rect::new(0, 0, 39, 40)), Screen::Minimal);
+        assert_eq!(Screen::from_size(Rect::new(0, 0, 19, 40)), Screen::TooSmall);
+    }
+
+    #[test]
+    fn should_use_theme_colours() {
+        let (io_tx, _io_rx) = tokio::sync::mpsc::channel(10);
+        let mut app = App::new(io_tx, Some(TestMode::Timed(Duration::from_secs(30))));
+        let theme = Theme {
+            name: "custom".to_owned(),
+            border: Color::Rgb(1, 2, 3),
+            ..Theme::default()
+        };
+        app.add_themes(vec![theme.clone()]);
+        assert!(app.select_theme("custom"));
+        app.initialized();
+        app.load_text("one two three".to_owned());
+
+        let mut terminal = Terminal::new(TestBackend::new(100, 40)).unwrap();
+        terminal.draw(|rect| draw(rect, &app)).unwrap();
+        let buffer = terminal.backend().buffer();
+        // The top left corner of the panel with the given title.
+        let corner = |title: &str| {
+            let width = buffer.area.width as usize;
+            let rows = buffer.content().chunks(width).collect::<Vec<_>>();
+            let (y, x) = rows
+                .iter()
+                .enumerate()
+                .find_map(|(y, row)| {
+                    let line = row
+                        .iter()
+                        .map(|cell| cell.symbol.as_str())
+                        .collect::<String>();
+                    line.find(title).map(|x| (y, line[..x].chars().count()))
+                })
+                .unwrap();
+            rows[y][x - 1].clone()
+        };
+
+        for title in ["Live", "Timer"] {
+            let corner = corner(title);
+            assert_eq!(corner.symbol, "┌", "{}", title);
+            assert_eq!(corner.fg, theme.border, "{}", title);
+        }
+    }
+
+    #[test]
+    fn should_draw_any_terminal_size() {
+        let (io_tx, _io_rx) = tokio::sync::mpsc::channel(10);
+        let mut app = App::new(io_tx, Some(TestMode::Passage));
+        let mut apps = vec![];
+
+        app.open_menu();
+        apps.push(app.state().clone());
+        app.initialized();
+        app.load_text("one two three".to_owned());
+        app.state.input(Input::Char('o'), app.now());
+        apps.push(app.state().clone());
+        app.load_text("o".to_owned());
+        app.state.input(Input::Char('o'), app.now());
+        apps.push(app.state().clone());
+
+        for state in apps {
+            app.state = state;
+            for (width, height) in [(1, 1), (19, 5), (20, 6), (45, 22), (52, 28), (200, 60)] {
+                let mut terminal = Terminal::new(TestBackend::new(width, height)).unwrap();
+                terminal.draw(|rect| draw(rect, &app)).unwrap();
+            }
+        }
+    }
 }
@@ -0,0 +1,475 @@
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+use chrono::Local;
+use clap::{Parser, Subcommand, ValueEnum};
+use eyre::{eyre, Result};
+use log::LevelFilter;
+
+use crate::app::config::Overrides;
+use crate::app::ghost::GhostRun;
+use crate::app::replay::Replay;
+use crate::app::ui::visible_glyph;
+use crate::engine::mode::TestMode;
+use crate::engine::stats::{KeyStat, TypingStats};
+use crate::io::history::{History, HistoryStore};
+use crate::io::library::TextLibrary;
+use crate::io::replays::ReplayStore;
+use crate::io::stats::StatsStore;
+
+/// Typing practice in the terminal.
+///
+/// Without a mode or a text, starts on the menu.
+#[derive(Debug, Parser)]
+#[command(name = "jackdull", version)]
+pub struct Cli {
+    /// Test to start, such as 30s, 50w or passage
+    #[arg(group = "test_mode")]
+    pub mode: Option<TestMode>,
+
+    /// Start a timed test of this many seconds
+    #[arg(
+        long,
+        value_name = "SECONDS",
+        group = "test_mode",
+        value_parser = clap::value_parser!(u64).range(1..)
+    )]
+    pub time: Option<u64>,
+
+    /// Start a test of this many words
+    #[arg(
+        long,
+        value_name = "COUNT",
+        group = "test_mode",
+        value_parser = clap::value_parser!(u64).range(1..)
+    )]
+    pub words: Option<u64>,
+
+    /// Start a test on the whole passage, untimed
+    #[arg(long, group = "test_mode")]
+    pub passage: bool,
+
+    /// Text to type: an id from `jackdull texts list`, or the path of a JSON
+    /// or plain text file
+    #[arg(long, value_name = "ID|FILE")]
+    pub text: Option<String>,
+
+    /// Colour theme, built-in or from the themes directory
+    #[arg(long, value_name = "NAME")]
+    pub theme: Option<String>,
+
+    /// Earlier run of the passage to race: off, best or last
+    #[arg(long, value_name = "RUN")]
+    pub ghost: Option<GhostRun>,
+
+    /// Directory of the texts to type
+    #[arg(long, value_name = "DIR")]
+    pub texts_dir: Option<PathBuf>,
+
+    /// Config file to read and save, instead of the one in the config
+    /// directory
+    #[arg(long, value_name = "PATH")]
+    pub config: Option<PathBuf>,
+
+    /// Messages shown in the log panel
+    #[arg(long, value_enum, default_value_t = LogLevel::Debug)]
+    pub log_level: LogLevel,
+
+    #[command(subcommand)]
+    pub command: Option<Command>,
+}
+
+#[derive(Debug, Subcommand)]
+pub enum Command {
+    #[command(flatten)]
+    Print(PrintCommand),
+    /// Play a recorded session back, the most recent one by default
+    Replay {
+        /// Replay file, from the replays directory
+        file: Option<PathBuf>,
+        /// How many times faster than it was typed
+        #[arg(long, default_value_t = 1.0, value_parser = parse_speed)]
+        speed: f64,
+    },
+}
+
+/// Subcommands that print to the terminal and exit, without starting the
+/// app.
+#[derive(Debug, Subcommand)]
+pub enum PrintCommand {
+    /// Print past sessions, most recent last
+    History {
+        /// Only sessions of this mode, such as 30s, 50w or passage
+        #[arg(long)]
+        mode: Option<TestMode>,
+        /// Only sessions on this text
+        #[arg(long, value_name = "ID")]
+        text: Option<String>,
+        /// Number of sessions to print
+        #[arg(long, default_value_t = 20)]
+        limit: usize,
+    },
+    /// Print the slowest and most missed keys of every session
+    Stats {
+        /// Number of keys in each ranking
+        #[arg(long, default_value_t = 10)]
+        count: usize,
+    },
+    /// Browse the texts to type
+    Texts {
+        #[command(subcommand)]
+        command: TextsCommand,
+    },
+}
+
+#[derive(Debug, Subcommand)]
+pub enum TextsCommand {
+    /// List the texts of the library
+    List,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum LogLevel {
+    Off,
+    Error,
+    Warn,
+    Info,
+    Debug,
+    Trace,
+}
+
+impl LogLevel {
+    pub fn filter(&self) -> LevelFilter {
+        match self {
+            LogLevel::Off => LevelFilter::Off,
+            LogLevel::Error => LevelFilter::Error,
+            LogLevel::Warn => LevelFilter::Warn,
+            LogLevel::Info => LevelFilter::Info,
+            LogLevel::Debug => LevelFilter::Debug,
+            LogLevel::Trace => LevelFilter::Trace,
+        }
+    }
+}
+
+impl Cli {
+    /// The test chosen with any of the mode options.
+    pub fn test_mode(&self) -> Option<TestMode> {
+        if let Some(seconds) = self.time {
+            Some(TestMode::Timed(Duration::from_secs(seconds)))
+        } else if let Some(goal) = self.words {
+            Some(TestMode::Words(goal as usize))
+        } else if self.passage {
+            Some(TestMode::Passage)
+        } else {
+            self.mode
+        }
+    }
+
+    /// Settings taking precedence over the config file. The text is left to
+    /// `resolve_text`, which needs the library.
+    pub fn overrides(&self) -> Overrides {
+        Overrides {
+            mode: self.test_mode(),
+            text: None,
+            theme: self.theme.clone(),
+            texts_dir: self.texts_dir.clone(),
+            ghost: self.ghost,
+        }
+    }
+}
+
+/// The id of the text given on the command line. A file is added to the
+/// library, anything else must be the id of a text in it.
+pub fn resolve_text(library: &mut TextLibrary, text: &str) -> Result<String> {
+    let path = Path::new(text);
+    if path.is_file() {
+        return Ok(library.add_file(path)?);
+    }
+
+    library.load()?;
+    match library.get(text) {
+        Some(found) => Ok(found.id.clone()),
+        None => Err(eyre!(
+            "no text '{}' in {}, run `jackdull texts list` to see them",
+            text,
+            library.dir().display()
+        )),
+    }
+}
+
+fn parse_speed(value: &str) -> Result<f64, String> {
+    match value.parse::<f64>() {
+        Ok(speed) if speed.is_finite() && speed >= 1.0 => Ok(speed),
+        _ => Err(format!("invalid speed '{}', expected 1 or more", value)),
+    }
+}
+
+/// The replay given on the command line, or the most recent one.
+pub fn load_replay(file: Option<&Path>) -> Result<Replay> {
+    match file {
+        Some(path) => ReplayStore::read(path),
+        None => {
+            let store = ReplayStore::default();
+            match store.last()? {
+                Some(path) => ReplayStore::read(&path),
+                None => Err(eyre!(
+                    "no replay in {} yet, finish a test to record one",
+                    store.dir().display()
+                )),
+            }
+        }
+    }
+}
+
+/// Runs a subcommand, printing to the standard output.
+pub fn run(command: &PrintCommand, library: &mut TextLibrary) -> Result<()> {
+    let mut out = io::stdout().lock();
+    match command {
+        PrintCommand::History { mode, text, limit } => {
+            let history = HistoryStore::default().load()?;
+            print_history(&mut out, &history, *mode, text.as_deref(), *limit)?
+        }
+        PrintCommand::Stats { count } => {
+            let stats = StatsStore::default().load()?;
+            print_stats(&mut out, &stats, *count)?
+        }
+        PrintCommand::Texts {
+            command: TextsCommand::List,
+        } => {
+            library.load()?;
+            print_texts(&mut out, library)?
+        }
+    }
+
+    Ok(())
+}
+
+pub fn print_history(
+    out: &mut impl Write,
+    history: &History,
+    mode: Option<TestMode>,
+    text: Option<&str>,
+    limit: usize,
+) -> io::Result<()> {
+    let query = history.query();
+    let query = match mode {
+        Some(mode) => query.mode(mode),
+        None => query,
+    };
+    let query = match text {
+        Some(text) => query.passage(text),
+        None => query,
+    };
+
+    let records = query.records();
+    if records.is_empty() {
+        return writeln!(out, "No session yet");
+    }
+
+    for record in query.last(limit) {
+        writeln!(
+            out,
+            "{}  {:<7}  #{:<4}  {:>3.0} WPM  {:>5.1}%",
+            record
+                .timestamp
+                .with_timezone(&Local)
+                .format("%Y-%m-%d %H:%M"),
+            record.mode.to_string(),
+            record.passage_id,
+            record.wpm,
+            record.accuracy
+        )?;
+    }
+
+    let average = |value: Option<f64>, unit: &str| {
+        value.map_or_else(|| "-".to_owned(), |value| format!("{:.1}{}", value, unit))
+    };
+    writeln!(
+        out,
+        "{} sessions, last 10: {} WPM {}",
+        records.len(),
+        average(query.average_wpm(10), ""),
+        average(query.average_accuracy(10), "%")
+    )?;
+    if let Some(best) = query.best() {
+        writeln!(out, "Best: {:.0} WPM in {} mode", best.wpm, best.mode)?;
+    }
+
+    Ok(())
+}
+
+pub fn print_stats(out: &mut impl Write, stats: &TypingStats, count: usize) -> io::Result<()> {
+    if stats.keys.is_empty() {
+        return writeln!(out, "No session yet");
+    }
+
+    let latencies = |stats: Vec<(String, KeyStat)>| {
+        stats
+            .into_iter()
+            .filter_map(|(name, stat)| {
+                stat.mean_latency()
+                    .map(|latency| format!("{} {}ms", name, latency.as_millis()))
+            })
+            .collect::<Vec<_>>()
+            .join(", ")
+    };
+    let keys = stats
+        .slowest_keys(count)
+        .into_iter()
+        .map(|(key, stat)| (visible_glyph(key).to_string(), stat))
+        .collect();
+    let bigrams = stats
+        .slowest_bigrams(count)
+        .into_iter()
+        .map(|(bigram, stat)| (bigram.chars().map(visible_glyph).collect(), stat))
+        .collect();
+    let missed = stats
+        .most_missed_keys(count)
+        .into_iter()
+        .map(|(key, stat)| format!("{} {:.0}%", visible_glyph(key), stat.error_rate() * 100.0))
+        .collect::<Vec<_>>()
+        .join(", ");
+
+    writeln!(out, "Slowest keys:    {}", latencies(keys))?;
+    writeln!(out, "Slowest bigrams: {}", latencies(bigrams))?;
+    writeln!(out, "Most missed:     {}", missed)
+}
+
+pub fn print_texts(out: &mut impl Write, library: &TextLibrary) -> io::Result<()> {
+    for text in library.list() {
+        writeln!(
+            out,
+            "{:<6}  {:>5} words  {}",
+            text.id, text.words_count, text.from
+        )?;
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::engine::score::Keystroke;
+    use crate::io::history::SessionRecord;
+
+    fn parse(args: &[&str]) -> Result<Cli, clap::Error> {
+        Cli::try_parse_from(std::iter::once("jackdull").chain(args.iter().copied()))
+    }
+
+    #[test]
+    fn should_pick_mode_from_options() {
+        let thirty = TestMode::Timed(Duration::from_secs(30));
+        assert_eq!(parse(&["--time", "30"]).unwrap().test_mode(), Some(thirty));
+        assert_eq!(parse(&["30s"]).unwrap().test_mode(), Some(thirty));
+        assert_eq!(
+            parse(&["--words", "50"]).unwrap().test_mode(),
+            Some(TestMode::Words(50))
+        );
+        assert_eq!(
+            parse(&["--passage"]).unwrap().test_mode(),
+            Some(TestMode::Passage)
+        );
+        assert_eq!(parse(&[]).unwrap().test_mode(), None);
+    }
+
+    #[test]
+    fn should_reject_invalid_options() {
+        assert!(parse(&["--time", "30", "--words", "50"]).is_err());
+        assert!(parse(&["30s", "--passage"]).is_err());
+        assert!(parse(&["--time", "0"]).is_err());
+        assert!(parse(&["forever"]).is_err());
+        assert!(parse(&["--ghost", "worst"]).is_err());
+        assert!(parse(&["--log-level", "loud"]).is_err());
+    }
+
+    #[test]
+    fn should_parse_subcommands() {
+        let cli = parse(&["history", "--mode", "50w", "--limit", "5"]).unwrap();
+        assert!(matches!(
+            cli.command,
+            Some(Command::Print(PrintCommand::History {
+                mode: Some(TestMode::Words(50)),
+                limit: 5,
+                ..
+            }))
+        ));
+
+        let cli = parse(&["--theme", "light", "--ghost", "best", "texts", "list"]).unwrap();
+        assert_eq!(cli.overrides().theme.as_deref(), Some("light"));
+        assert_eq!(cli.overrides().ghost, Some(GhostRun::Best));
+        assert!(matches!(
+            cli.command,
+            Some(Command::Print(PrintCommand::Texts {
+                command: TextsCommand::List
+            }))
+        ));
+    }
+
+    #[test]
+    fn should_resolve_text_by_id_or_file() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("drill.txt");
+        std::fs::write(&path, "asdf jkl;").unwrap();
+        let mut library = TextLibrary::new(dir.path().join("texts"));
+
+        assert_eq!(resolve_text(&mut library, "1").unwrap(), "1");
+        assert_eq!(
+            resolve_text(&mut library, path.to_str().unwrap()).unwrap(),
+            "drill"
+        );
+        let error = resolve_text(&mut library, "42").unwrap_err().to_string();
+        assert!(error.starts_with("no text '42' in "));
+    }
+
+    #[test]
+    fn should_print_history() {
+        let timed = TestMode::Timed(Duration::from_secs(30));
+        let history = History::from(vec![
+            SessionRecord::sample("1", timed, 40.0),
+            SessionRecord::sample("2", TestMode::Passage, 80.0),
+            SessionRecord::sample("1", timed, 60.0),
+        ]);
+
+        let mut out = vec![];
+        print_history(&mut out, &history, Some(timed), None, 1).unwrap();
+        let out = String::from_utf8(out).unwrap();
+        let lines = out.lines().collect::<Vec<_>>();
+
+        assert_eq!(lines.len(), 3);
+        assert!(lines[0].ends_with("30s      #1      60 WPM  100.0%"));
+        assert_eq!(lines[1], "2 sessions, last 10: 50.0 WPM 100.0%");
+        assert_eq!(lines[2], "Best: 60 WPM in 30s mode");
+
+        let mut out = vec![];
+        print_history(&mut out, &history, None, Some("3"), 10).unwrap();
+        assert_eq!(String::from_utf8(out).unwrap(), "No session yet\n");
+    }
+
+    #[test]
+    fn should_print_stats_and_texts() {
+        let stats = TypingStats::compute(
+            "a b",
+            &[
+                Keystroke::typed(0, 'a', 'a', 0),
+                Keystroke::typed(1, ' ', 'x', 200),
+            ],
+        );
+
+        let mut out = vec![];
+        print_stats(&mut out, &stats, 5).unwrap();
+        assert_eq!(
+            String::from_utf8(out).unwrap(),
+            "Slowest keys:    · 200ms\n\
+             Slowest bigrams: a· 200ms\n\
+             Most missed:     · 100%\n"
+        );
+
+        let mut library = TextLibrary::new("./does-not-exist");
+        library.load().unwrap();
+        let mut out = vec![];
+        print_texts(&mut out, &library).unwrap();
+        assert!(String::from_utf8(out).unwrap().starts_with("1 "));
+    }
+}
@@ -0,0 +1,503 @@
+//! The rules of a typing test, without any terminal or runtime: the engine
+//! is fed timestamped keystrokes and ticks, and tells when a word is typed
+//! and when the test ends.
+
+use std::time::{Duration, Instant};
+
+use serde::{Deserialize, Serialize};
+
+use self::mode::TestMode;
+use self::score::{completed_words, EndReason, Keystroke, Score, TestResult, CHARS_PER_WORD};
+use self::stats::TypingStats;
+
+pub mod mode;
+pub mod score;
+pub mod stats;
+
+/// Measures a typing session from a monotonic start instant, so the elapsed
+/// time never drifts whatever the redraw rate. Time spent paused is not
+/// counted.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct SessionClock {
+    started_at: Option<Instant>,
+    paused_at: Option<Instant>,
+    paused: Duration,
+}
+
+impl SessionClock {
+    pub fn start(&mut self, now: Instant) {
+        self.started_at.get_or_insert(now);
+    }
+
+    pub fn is_started(&self) -> bool {
+        self.started_at.is_some()
+    }
+
+    pub fn is_paused(&self) -> bool {
+        self.paused_at.is_some()
+    }
+
+    pub fn pause(&mut self, now: Instant) {
+        if self.is_started() {
+            self.paused_at.get_or_insert(now);
+        }
+    }
+
+    pub fn resume(&mut self, now: Instant) {
+        if let Some(paused_at) = self.paused_at.take() {
+            self.paused += now.saturating_duration_since(paused_at);
+        }
+    }
+
+    pub fn elapsed(&self, now: Instant) -> Duration {
+        match self.started_at {
+            Some(started_at) => {
+                let now = self.paused_at.unwrap_or(now);
+                now.saturating_duration_since(started_at)
+                    .saturating_sub(self.paused)
+            }
+            None => Duration::ZERO,
+        }
+    }
+
+    pub fn remaining(&self, limit: Duration, now: Instant) -> Duration {
+        limit.saturating_sub(self.elapsed(now))
+    }
+}
+
+/// A key pressed during a test.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Input {
+    Char(char),
+    Backspace,
+}
+
+/// What an input or a tick did to the test.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum EngineEvent {
+    /// The last character of a word is typed, `count` words are typed so far.
+    WordCompleted { count: usize },
+    /// The whole target text is typed.
+    Finished,
+    /// The time limit of a timed test is reached.
+    TimeUp,
+}
+
+/// A typing test: the target text, what is typed over it and when. The
+/// clock starts with the first typed character, and the test ends by itself
+/// once the text is typed or the time is up.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Engine {
+    mode: TestMode,
+    target: String,
+    typed: String,
+    keystrokes: Vec<Keystroke>,
+    clock: SessionClock,
+    words: usize,
+    /// Gross WPM of each second of typing so far.
+    wpm_samples: Vec<u64>,
+    result: Option<TestResult>,
+}
+
+impl Engine {
+    pub fn new(mode: TestMode, target: String) -> Self {
+        Self {
+            mode,
+            target,
+            typed: String::new(),
+            keystrokes: vec![],
+            clock: SessionClock::default(),
+            words: 0,
+            wpm_samples: vec![],
+            result: None,
+        }
+    }
+
+    /// A fresh test with the same mode and target.
+    pub fn restarted(&self) -> Self {
+        Self::new(self.mode, self.target.clone())
+    }
+
+    pub fn mode(&self) -> TestMode {
+        self.mode
+    }
+
+    pub fn target(&self) -> &str {
+        &self.target
+    }
+
+    pub fn typed(&self) -> &str {
+        &self.typed
+    }
+
+    /// Position of the next character to type.
+    pub fn cursor(&self) -> usize {
+        self.typed.chars().count()
+    }
+
+    /// Wrong characters left in the typed text.
+    pub fn errors(&self) -> usize {
+        self.typed
+            .chars()
+            .zip(self.target.chars())
+            .filter(|(typed, expected)| typed != expected)
+            .count()
+    }
+
+    pub fn keystrokes(&self) -> &[Keystroke] {
+        &self.keystrokes
+    }
+
+    pub fn result(&self) -> Option<&TestResult> {
+        self.result.as_ref()
+    }
+
+    pub fn is_running(&self) -> bool {
+        self.result.is_none()
+    }
+
+    pub fn is_paused(&self) -> bool {
+        self.is_running() && self.clock.is_paused()
+    }
+
+    pub fn elapsed(&self, now: Instant) -> Duration {
+        self.clock.elapsed(now)
+    }
+
+    /// Time left in a timed test.
+    pub fn remaining(&self, now: Instant) -> Option<Duration> {
+        self.mode
+            .time_limit()
+            .map(|limit| self.clock.remaining(limit, now))
+    }
+
+    /// Number of words typed so far, and number of words to type.
+    pub fn words_progress(&self) -> (usize, usize) {
+        let total = self.target.split_whitespace().count();
+        let goal = self.mode.word_goal().map_or(total, |goal| goal.min(total));
+        (self.words, goal)
+    }
+
+    /// Ratio of the target text already typed, between 0 and 1.
+    pub fn text_progress(&self) -> f64 {
+        let total = self.target.chars().count();
+        if total > 0 {
+            self.cursor().min(total) as f64 / total as f64
+        } else {
+            0.0
+        }
+    }
+
+    pub fn score(&self, now: Instant) -> Score {
+        Score::compute(
+            &self.typed,
+            &self.target,
+            &self.keystrokes,
+            self.elapsed(now),
+        )
+    }
+
+    /// Per-key and per-bigram statistics of the keystrokes so far.
+    pub fn typing_stats(&self) -> TypingStats {
+        TypingStats::compute(&self.target, &self.keystrokes)
+    }
+
+    pub fn wpm_samples(&self) -> &[u64] {
+        &self.wpm_samples
+    }
+
+    pub fn toggle_pause(&mut self, now: Instant) {
+        if !self.is_running() {
+            return;
+        }
+        if self.clock.is_paused() {
+            self.clock.resume(now);
+        } else {
+            self.clock.pause(now);
+        }
+    }
+
+    /// Types or deletes a character. The test ends once the whole target or
+    /// the word goal is typed. Inputs are ignored once the test has ended or
+    /// while it is paused, and an input coming after the time limit ends the
+    /// test instead.
+    pub fn input(&mut self, input: Input, now: Instant) -> Vec<EngineEvent> {
+        if !self.is_running() || self.clock.is_paused() {
+            return vec![];
+        }
+        let mut events = self.tick(now);
+        if !events.is_empty() {
+            return events;
+        }
+
+        match input {
+            Input::Char(c) => {
+                self.clock.start(now);
+                let position = self.cursor();
+                self.keystrokes.push(Keystroke::Typed {
+                    position,
+                    expected: self.target.chars().nth(position),
+                    typed: c,
+                    at: self.clock.elapsed(now),
+                });
+                self.typed.push(c);
+            }
+            Input::Backspace => {
+                if self.typed.pop().is_some() {
+                    self.keystrokes.push(Keystroke::Deleted {
+                        position: self.cursor(),
+                        at: self.clock.elapsed(now),
+                    });
+                }
+            }
+        }
+
+        let words = completed_words(&self.typed, &self.target);
+        if words > self.words {
+            events.push(EngineEvent::WordCompleted { count: words });
+        }
+        self.words = words;
+
+        let goal_reached = self.mode.word_goal().is_some_and(|goal| words >= goal);
+        let text_typed = !self.target.is_empty() && self.cursor() >= self.target.chars().count();
+        if goal_reached || text_typed {
+            self.end(EndReason::Finished, now);
+            events.push(EngineEvent::Finished);
+        }
+        events
+    }
+
+    /// Samples the speed of each second elapsed since the last tick, and
+    /// ends a timed test once its time is up.
+    pub fn tick(&mut self, now: Instant) -> Vec<EngineEvent> {
+        if !self.is_running() {
+            return vec![];
+        }
+        self.sample_wpm(now);
+
+        let elapsed = self.clock.elapsed(now);
+        if self.mode.time_limit().is_some_and(|limit| elapsed >= limit) {
+            self.end(EndReason::TimeUp, now);
+            vec![EngineEvent::TimeUp]
+        } else {
+            vec![]
+        }
+    }
+
+    fn sample_wpm(&mut self, now: Instant) {
+        let seconds = self.clock.elapsed(now).as_secs() as usize;
+        while self.wpm_samples.len() < seconds {
+            let second = self.wpm_samples.len() as u64;
+            let typed = self
+                .keystrokes
+                .iter()
+                .filter(|keystroke| matches!(keystroke, Keystroke::Typed { .. }))
+                .filter(|keystroke| keystroke.at().as_secs() == second)
+                .count();
+            self.wpm_samples
+                .push((typed as f64 * 60.0 / CHARS_PER_WORD).round() as u64);
+        }
+    }
+
+    fn end(&mut self, reason: EndReason, now: Instant) {
+        let mut score = self.score(now);
+        if let (EndReason::TimeUp, Some(limit)) = (reason, self.mode.time_limit()) {
+            score.elapsed = score.elapsed.min(limit);
+        }
+        self.clock.pause(now);
+        self.result = Some(TestResult { reason, score });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn ms(millis: u64) -> Duration {
+        Duration::from_millis(millis)
+    }
+
+    fn type_text(engine: &mut Engine, text: &str, start: Instant) -> Vec<EngineEvent> {
+        text.chars()
+            .enumerate()
+            .flat_map(|(i, c)| engine.input(Input::Char(c), start + ms(100 * i as u64)))
+            .collect()
+    }
+
+    #[test]
+    fn should_finish_when_cursor_reaches_the_end() {
+        let start = Instant::now();
+        let mut engine = Engine::new(TestMode::Passage, "ab".to_owned());
+
+        assert_eq!(engine.input(Input::Char('a'), start), vec![]);
+        assert_eq!(
+            engine.input(Input::Char('x'), start + ms(250)),
+            vec![
+                EngineEvent::WordCompleted { count: 1 },
+                EngineEvent::Finished
+            ]
+        );
+
+        let result = engine.result().unwrap();
+        assert_eq!(result.reason, EndReason::Finished);
+        assert_eq!(result.score.elapsed, ms(250));
+        assert_eq!(engine.errors(), 1);
+    }
+
+    #[test]
+    fn should_report_completed_words() {
+        let start = Instant::now();
+        let mut engine = Engine::new(TestMode::Passage, "one two three".to_owned());
+
+        let events = type_text(&mut engine, "one tw", start);
+        assert_eq!(events, vec![EngineEvent::WordCompleted { count: 1 }]);
+        assert_eq!(engine.words_progress(), (1, 3));
+        assert_eq!(engine.text_progress(), 6.0 / 13.0);
+
+        engine.input(Input::Backspace, start + ms(600));
+        engine.input(Input::Backspace, start + ms(700));
+        engine.input(Input::Backspace, start + ms(800));
+        engine.input(Input::Backspace, start + ms(900));
+        assert_eq!(engine.words_progress(), (0, 3));
+        assert_eq!(engine.cursor(), 2);
+    }
+
+    #[test]
+    fn should_finish_at_the_word_goal() {
+        let start = Instant::now();
+        let mut engine = Engine::new(TestMode::Words(2), "one two three four".to_owned());
+        assert_eq!(engine.words_progress(), (0, 2));
+
+        type_text(&mut engine, "one t", start);
+        assert!(engine.is_running());
+
+        let events = type_text(&mut engine, "wo", start + ms(500));
+        assert_eq!(
+            events,
+            vec![
+                EngineEvent::WordCompleted { count: 2 },
+                EngineEvent::Finished
+            ]
+        );
+        assert_eq!(engine.result().unwrap().reason, EndReason::Finished);
+        assert_eq!(engine.words_progress(), (2, 2));
+    }
+
+    #[test]
+    fn should_end_when_time_is_up() {
+        let start = Instant::now();
+        let mut engine = Engine::new(TestMode::Timed(Duration::from_secs(2)), "abc".to_owned());
+
+        assert_eq!(engine.tick(start + Duration::from_secs(5)), vec![]);
+        engine.input(Input::Char('a'), start);
+        assert_eq!(engine.tick(start + ms(1500)), vec![]);
+        assert_eq!(engine.remaining(start + ms(1500)), Some(ms(500)));
+
+        assert_eq!(
+            engine.input(Input::Char('b'), start + ms(2100)),
+            vec![EngineEvent::TimeUp]
+        );
+        assert_eq!(engine.typed(), "a");
+        assert_eq!(engine.result().unwrap().score.elapsed, ms(2000));
+        assert_eq!(engine.input(Input::Char('b'), start + ms(2200)), vec![]);
+    }
+
+    #[test]
+    fn should_ignore_inputs_while_paused() {
+        let start = Instant::now();
+        let mut engine = Engine::new(TestMode::Timed(Duration::from_secs(2)), "abc".to_owned());
+        engine.input(Input::Char('a'), start);
+        engine.toggle_pause(start + ms(500));
+
+        assert!(engine.is_paused());
+        assert_eq!(engine.input(Input::Char('b'), start + ms(600)), vec![]);
+        assert_eq!(engine.tick(start + Duration::from_secs(10)), vec![]);
+
+        engine.toggle_pause(start + Duration::from_secs(10));
+        engine.input(Input::Char('b'), start + ms(10_200));
+        assert_eq!(engine.typed(), "ab");
+        assert_eq!(engine.keystrokes()[1].at(), ms(700));
+    }
+
+    #[test]
+    fn should_restart_with_same_text() {
+        let start = Instant::now();
+        let mut engine = Engine::new(TestMode::Passage, "ab".to_owned());
+        type_text(&mut engine, "ab", start);
+
+        let engine = engine.restarted();
+        assert_eq!(engine.target(), "ab");
+        assert_eq!(engine.typed(), "");
+        assert_eq!(engine.elapsed(start + ms(500)), Duration::ZERO);
+        assert!(engine.is_running());
+    }
+
+    #[test]
+    fn should_sample_wpm_each_second() {
+        let start = Instant::now();
+        let mut engine = Engine::new(TestMode::Passage, "abcd".to_owned());
+        engine.tick(start);
+        assert!(engine.wpm_samples().is_empty());
+
+        for (c, at) in [('a', 0), ('b', 800), ('c', 1200)] {
+            engine.input(Input::Char(c), start + ms(at));
+        }
+        engine.tick(start + ms(2500));
+        assert_eq!(engine.wpm_samples(), &[24, 12]);
+    }
+
+    #[test]
+    fn should_not_count_time_before_start() {
+        let clock = SessionClock::default();
+        assert_eq!(clock.elapsed(Instant::now()), Duration::ZERO);
+    }
+
+    #[test]
+    fn should_measure_elapsed_from_start() {
+        let start = Instant::now();
+        let mut clock = SessionClock::default();
+        clock.start(start);
+        clock.start(start + Duration::from_secs(5));
+
+        let now = start + Duration::from_millis(1500);
+        assert_eq!(clock.elapsed(now), Duration::from_millis(1500));
+        assert_eq!(
+            clock.remaining(Duration::from_secs(60), now),
+            Duration::from_millis(58500)
+        );
+    }
+
+    #[test]
+    fn should_not_count_paused_time() {
+        let start = Instant::now();
+        let mut clock = SessionClock::default();
+        clock.start(start);
+        clock.pause(start + Duration::from_secs(2));
+
+        assert!(clock.is_paused());
+        assert_eq!(
+            clock.elapsed(start + Duration::from_secs(10)),
+            Duration::from_secs(2)
+        );
+
+        clock.resume(start + Duration::from_secs(10));
+        assert_eq!(
+            clock.elapsed(start + Duration::from_secs(11)),
+            Duration::from_secs(3)
+        );
+    }
+
+    #[test]
+    fn should_not_have_negative_remaining_time() {
+        let start = Instant::now();
+        let mut clock = SessionClock::default();
+        clock.start(start);
+
+        let now = start + Duration::from_secs(61);
+        assert_eq!(
+            clock.remaining(Duration::from_secs(60), now),
+            Duration::ZERO
+        );
+    }
+}
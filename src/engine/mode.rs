@@ -0,0 +1,157 @@
+use std::fmt::{self, Display};
+use std::str::FromStr;
+use std::time::Duration;
+
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+/// Lengths offered for timed tests, in seconds.
+pub const DURATIONS: [u64; 4] = [15, 30, 60, 120];
+
+/// Word goals offered for word-count tests.
+pub const WORD_GOALS: [usize; 4] = [10, 25, 50, 100];
+
+/// How a typing test ends.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum TestMode {
+    /// Ends when the time is up, or when the whole text is typed.
+    Timed(Duration),
+    /// Ends once the given number of words is typed.
+    Words(usize),
+    /// Ends once the whole passage is typed, whatever the time it takes.
+    Passage,
+}
+
+impl TestMode {
+    pub fn time_limit(&self) -> Option<Duration> {
+        if let TestMode::Timed(limit) = self {
+            Some(*limit)
+        } else {
+            None
+        }
+    }
+
+    pub fn word_goal(&self) -> Option<usize> {
+        if let TestMode::Words(goal) = self {
+            Some(*goal)
+        } else {
+            None
+        }
+    }
+
+    /// The length of the test spelled out, such as `30 seconds`.
+    pub fn label(&self) -> String {
+        match self {
+            TestMode::Timed(limit) => format!("{} seconds", limit.as_secs()),
+            TestMode::Words(goal) => format!("{} words", goal),
+            TestMode::Passage => "whole passage".to_owned(),
+        }
+    }
+}
+
+impl Default for TestMode {
+    fn default() -> Self {
+        TestMode::Timed(Duration::from_secs(60))
+    }
+}
+
+impl Display for TestMode {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            TestMode::Timed(limit) => write!(f, "{}s", limit.as_secs()),
+            TestMode::Words(goal) => write!(f, "{}w", goal),
+            TestMode::Passage => write!(f, "passage"),
+        }
+    }
+}
+
+impl FromStr for TestMode {
+    type Err = String;
+
+    /// Parses `30s` (or `30`) as a timed test, `50w` as a word-count test
+    /// and `passage` as a full-passage test.
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        let invalid = || {
+            format!(
+                "invalid mode '{}', expected <seconds>s, <words>w or passage",
+                value
+            )
+        };
+        let value = value.trim();
+
+        if value == "passage" {
+            return Ok(TestMode::Passage);
+        }
+        if let Some(goal) = value.strip_suffix('w') {
+            return match goal.parse::<usize>() {
+                Ok(goal) if goal > 0 => Ok(TestMode::Words(goal)),
+                _ => Err(invalid()),
+            };
+        }
+        let seconds = value.strip_suffix('s').unwrap_or(value);
+        match seconds.parse::<u64>() {
+            Ok(seconds) if seconds > 0 => Ok(TestMode::Timed(Duration::from_secs(seconds))),
+            _ => Err(invalid()),
+        }
+    }
+}
+
+/// Stored as its textual form, such as `30s`, `50w` or `passage`.
+impl Serialize for TestMode {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        serializer.serialize_str(&self.to_string())
+    }
+}
+
+impl<'de> Deserialize<'de> for TestMode {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        let value = String::deserialize(deserializer)?;
+        value.parse().map_err(serde::de::Error::custom)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn should_parse_modes() {
+        assert_eq!("30s".parse(), Ok(TestMode::Timed(Duration::from_secs(30))));
+        assert_eq!("120".parse(), Ok(TestMode::Timed(Duration::from_secs(120))));
+        assert_eq!("50w".parse(), Ok(TestMode::Words(50)));
+        assert_eq!("passage".parse(), Ok(TestMode::Passage));
+    }
+
+    #[test]
+    fn should_reject_invalid_modes() {
+        assert!("0s".parse::<TestMode>().is_err());
+        assert!("w".parse::<TestMode>().is_err());
+        assert!("fast".parse::<TestMode>().is_err());
+    }
+
+    #[test]
+    fn should_serialize_as_string() {
+        let mode = TestMode::Words(50);
+        let json = serde_json::to_string(&mode).unwrap();
+
+        assert_eq!(json, r#""50w""#);
+        assert_eq!(serde_json::from_str::<TestMode>(&json).unwrap(), mode);
+        assert!(serde_json::from_str::<TestMode>(r#""fast""#).is_err());
+    }
+
+    #[test]
+    fn should_display_as_parsable_value() {
+        for mode in [
+            TestMode::Timed(Duration::from_secs(15)),
+            TestMode::Words(25),
+            TestMode::Passage,
+        ] {
+            assert_eq!(mode.to_string().parse(), Ok(mode));
+        }
+    }
+}
@@ -0,0 +1,316 @@
+use std::fmt::{self, Display};
+use std::time::Duration;
+
+use serde::{Deserialize, Serialize};
+
+/// Standard definition of a word when measuring typing speed.
+pub const CHARS_PER_WORD: f64 = 5.0;
+
+/// A single key press, recorded against the position it was typed at and the
+/// time since the start of the session.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Keystroke {
+    Typed {
+        position: usize,
+        expected: Option<char>,
+        typed: char,
+        at: Duration,
+    },
+    Deleted {
+        position: usize,
+        at: Duration,
+    },
+}
+
+impl Keystroke {
+    pub fn at(&self) -> Duration {
+        match self {
+            Keystroke::Typed { at, .. } | Keystroke::Deleted { at, .. } => *at,
+        }
+    }
+
+    pub fn is_correct(&self) -> bool {
+        matches!(self, Keystroke::Typed { expected: Some(expected), typed, .. } if expected == typed)
+    }
+
+    /// `typed` where `expected` was due, `at_ms` milliseconds in, for tests.
+    #[cfg(test)]
+    pub fn typed(position: usize, expected: char, typed: char, at_ms: u64) -> Self {
+        Keystroke::Typed {
+            position,
+            expected: Some(expected),
+            typed,
+            at: Duration::from_millis(at_ms),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Accuracy {
+    /// Every typed character, backspaces excluded.
+    pub total_keystrokes: usize,
+    pub correct_keystrokes: usize,
+    /// Wrong characters that were later removed with backspace.
+    pub corrected_errors: usize,
+    /// Wrong characters still present in the final text.
+    pub uncorrected_errors: usize,
+    pub percentage: f64,
+}
+
+impl Accuracy {
+    pub fn compute(keystrokes: &[Keystroke]) -> Self {
+        let mut total_keystrokes = 0;
+        let mut correct_keystrokes = 0;
+        let mut corrected_errors = 0;
+        let mut typed: Vec<bool> = vec![];
+
+        for keystroke in keystrokes {
+            match keystroke {
+                Keystroke::Typed { position, .. } => {
+                    total_keystrokes += 1;
+                    if keystroke.is_correct() {
+                        correct_keystrokes += 1;
+                    }
+                    typed.truncate(*position);
+                    typed.push(keystroke.is_correct());
+                }
+                Keystroke::Deleted { position, .. } => {
+                    if typed.len() > *position && !typed[*position] {
+                        corrected_errors += 1;
+                    }
+                    typed.truncate(*position);
+                }
+            }
+        }
+
+        let uncorrected_errors = typed.iter().filter(|correct| !**correct).count();
+        let percentage = if total_keystrokes > 0 {
+            100.0 * correct_keystrokes as f64 / total_keystrokes as f64
+        } else {
+            100.0
+        };
+
+        Self {
+            total_keystrokes,
+            correct_keystrokes,
+            corrected_errors,
+            uncorrected_errors,
+            percentage,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Score {
+    pub elapsed: Duration,
+    pub typed_chars: usize,
+    pub correct_chars: usize,
+    /// Every typed character, five characters per word.
+    pub gross_wpm: f64,
+    /// Gross WPM minus the errors left in the text, per minute.
+    pub net_wpm: f64,
+    /// Correct characters per minute.
+    pub cpm: f64,
+    pub accuracy: Accuracy,
+}
+
+impl Score {
+    pub fn compute(typed: &str, target: &str, keystrokes: &[Keystroke], elapsed: Duration) -> Self {
+        let typed_chars = typed.chars().count();
+        let correct_chars = typed
+            .chars()
+            .zip(target.chars())
+            .filter(|(typed, expected)| typed == expected)
+            .count();
+        let errors = typed_chars - correct_chars;
+
+        let minutes = elapsed.as_secs_f64() / 60.0;
+        let per_minute = |count: f64| {
+            if minutes > 0.0 {
+                count / minutes
+            } else {
+                0.0
+            }
+        };
+
+        let gross_wpm = per_minute(typed_chars as f64 / CHARS_PER_WORD);
+        let net_wpm = (gross_wpm - per_minute(errors as f64)).max(0.0);
+        let cpm = per_minute(correct_chars as f64);
+
+        Self {
+            elapsed,
+            typed_chars,
+            correct_chars,
+            gross_wpm,
+            net_wpm,
+            cpm,
+            accuracy: Accuracy::compute(keystrokes),
+        }
+    }
+}
+
+impl Display for Score {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{:.0} WPM (raw {:.0}), {:.0} CPM, {:.1}% accuracy in {:.3}s",
+            self.net_wpm,
+            self.gross_wpm,
+            self.cpm,
+            self.accuracy.percentage,
+            self.elapsed.as_secs_f64()
+        )
+    }
+}
+
+/// Number of target words the cursor moved past the end of.
+pub fn completed_words(typed: &str, target: &str) -> usize {
+    let typed_len = typed.chars().count();
+    let target = target.chars().collect::<Vec<_>>();
+
+    target
+        .iter()
+        .enumerate()
+        .take(typed_len)
+        .filter(|(i, c)| {
+            !c.is_whitespace() && target.get(i + 1).is_none_or(|next| next.is_whitespace())
+        })
+        .count()
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum EndReason {
+    Finished,
+    TimeUp,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct TestResult {
+    pub reason: EndReason,
+    pub score: Score,
+}
+
+impl Display for TestResult {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.reason {
+            EndReason::Finished => write!(f, "Finished! Your speed is {}", self.score),
+            EndReason::TimeUp => write!(f, "Time is up! Your speed is {}", self.score),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn should_use_five_characters_per_word() {
+        let text = "a".repeat(50);
+        let score = Score::compute(&text, &text, &[], Duration::from_secs(60));
+
+        assert_eq!(score.gross_wpm, 10.0);
+        assert_eq!(score.net_wpm, 10.0);
+        assert_eq!(score.cpm, 50.0);
+    }
+
+    #[test]
+    fn should_use_millisecond_precision() {
+        let score = Score::compute("hello", "hello", &[], Duration::from_millis(1500));
+
+        assert_eq!(score.gross_wpm, 40.0);
+        assert_eq!(score.elapsed.as_millis(), 1500);
+    }
+
+    #[test]
+    fn should_subtract_uncorrected_errors_from_net_wpm() {
+        let score = Score::compute("hellx worlx", "hello world", &[], Duration::from_secs(30));
+
+        assert_eq!(score.typed_chars, 11);
+        assert_eq!(score.correct_chars, 9);
+        assert!((score.gross_wpm - 4.4).abs() < f64::EPSILON);
+        assert!((score.net_wpm - 0.4).abs() < 1e-9);
+    }
+
+    #[test]
+    fn should_not_divide_by_zero() {
+        let score = Score::compute("", "hello", &[], Duration::ZERO);
+
+        assert_eq!(score.gross_wpm, 0.0);
+        assert_eq!(score.net_wpm, 0.0);
+        assert_eq!(score.cpm, 0.0);
+    }
+
+    #[test]
+    fn should_count_completed_words() {
+        assert_eq!(completed_words("", "one two"), 0);
+        assert_eq!(completed_words("on", "one two"), 0);
+        assert_eq!(completed_words("one", "one two"), 1);
+        assert_eq!(completed_words("one t", "one two"), 1);
+        assert_eq!(completed_words("one two", "one two"), 2);
+        assert_eq!(completed_words("xxx yyy", "one two"), 2);
+    }
+
+    #[test]
+    fn should_compute_accuracy_without_errors() {
+        let keystrokes = [
+            Keystroke::typed(0, 'a', 'a', 0),
+            Keystroke::typed(1, 'b', 'b', 0),
+        ];
+        let accuracy = Accuracy::compute(&keystrokes);
+
+        assert_eq!(accuracy.total_keystrokes, 2);
+        assert_eq!(accuracy.correct_keystrokes, 2);
+        assert_eq!(accuracy.percentage, 100.0);
+    }
+
+    #[test]
+    fn should_count_corrected_and_uncorrected_errors() {
+        let keystrokes = [
+            Keystroke::typed(0, 'a', 'x', 0),
+            Keystroke::Deleted {
+                position: 0,
+                at: Duration::ZERO,
+            },
+            Keystroke::typed(0, 'a', 'a', 0),
+            Keystroke::typed(1, 'b', 'y', 0),
+            Keystroke::typed(2, 'c', 'c', 0),
+        ];
+        let accuracy = Accuracy::compute(&keystrokes);
+
+        assert_eq!(accuracy.total_keystrokes, 4);
+        assert_eq!(accuracy.correct_keystrokes, 2);
+        assert_eq!(accuracy.corrected_errors, 1);
+        assert_eq!(accuracy.uncorrected_errors, 1);
+        assert_eq!(accuracy.percentage, 50.0);
+    }
+
+    #[test]
+    fn should_not_count_deleted_correct_characters_as_corrected_errors() {
+        let keystrokes = [
+            Keystroke::typed(0, 'a', 'a', 0),
+            Keystroke::Deleted {
+                position: 0,
+                at: Duration::ZERO,
+            },
+        ];
+        let accuracy = Accuracy::compute(&keystrokes);
+
+        assert_eq!(accuracy.corrected_errors, 0);
+        assert_eq!(accuracy.uncorrected_errors, 0);
+    }
+
+    #[test]
+    fn should_count_characters_typed_past_the_end_as_errors() {
+        let keystrokes = [Keystroke::Typed {
+            position: 0,
+            expected: None,
+            typed: 'a',
+            at: Duration::ZERO,
+        }];
+        let accuracy = Accuracy::compute(&keystrokes);
+
+        assert_eq!(accuracy.uncorrected_errors, 1);
+        assert_eq!(accuracy.percentage, 0.0);
+    }
+}
@@ -0,0 +1,245 @@
+use std::collections::BTreeMap;
+use std::time::Duration;
+
+use serde::{Deserialize, Serialize};
+
+use crate::engine::score::Keystroke;
+
+/// Error and latency figures for a key, or for a bigram.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct KeyStat {
+    /// Characters typed where this key was expected.
+    pub count: u64,
+    pub errors: u64,
+    /// Characters typed with a known interval since the previous keystroke.
+    pub timed_count: u64,
+    pub total_latency_ms: u64,
+}
+
+impl KeyStat {
+    fn record(&mut self, correct: bool, latency: Option<Duration>) {
+        self.count += 1;
+        if !correct {
+            self.errors += 1;
+        }
+        if let Some(latency) = latency {
+            self.timed_count += 1;
+            self.total_latency_ms += latency.as_millis() as u64;
+        }
+    }
+
+    pub fn merge(&mut self, other: &KeyStat) {
+        self.count += other.count;
+        self.errors += other.errors;
+        self.timed_count += other.timed_count;
+        self.total_latency_ms += other.total_latency_ms;
+    }
+
+    pub fn mean_latency(&self) -> Option<Duration> {
+        (self.timed_count > 0)
+            .then(|| Duration::from_millis(self.total_latency_ms / self.timed_count))
+    }
+
+    /// Ratio of wrong characters, between 0 and 1.
+    pub fn error_rate(&self) -> f64 {
+        if self.count > 0 {
+            self.errors as f64 / self.count as f64
+        } else {
+            0.0
+        }
+    }
+}
+
+/// Per-key and per-bigram statistics. Each typed character is attributed to
+/// the target character it was expected to be, and to the bigram of target
+/// characters it completes, along with the time since the previous keystroke.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TypingStats {
+    pub keys: BTreeMap<char, KeyStat>,
+    pub bigrams: BTreeMap<String, KeyStat>,
+}
+
+impl TypingStats {
+    pub fn compute(target: &str, keystrokes: &[Keystroke]) -> Self {
+        let target = target.chars().collect::<Vec<_>>();
+        let mut stats = Self::default();
+        let mut previous_at: Option<Duration> = None;
+
+        for keystroke in keystrokes {
+            let at = keystroke.at();
+            let latency = previous_at.map(|previous| at.saturating_sub(previous));
+            previous_at = Some(at);
+
+            let Keystroke::Typed {
+                position,
+                expected: Some(expected),
+                ..
+            } = keystroke
+            else {
+                continue;
+            };
+            let correct = keystroke.is_correct();
+
+            stats
+                .keys
+                .entry(*expected)
+                .or_default()
+                .record(correct, latency);
+            if let Some(before) = position.checked_sub(1).and_then(|i| target.get(i)) {
+                stats
+                    .bigrams
+                    .entry(format!("{}{}", before, expected))
+                    .or_default()
+                    .record(correct, latency);
+            }
+        }
+
+        stats
+    }
+
+    pub fn merge(&mut self, other: &TypingStats) {
+        for (key, stat) in &other.keys {
+            self.keys.entry(*key).or_default().merge(stat);
+        }
+        for (bigram, stat) in &other.bigrams {
+            self.bigrams.entry(bigram.clone()).or_default().merge(stat);
+        }
+    }
+
+    pub fn key(&self, key: char) -> Option<&KeyStat> {
+        self.keys.get(&key)
+    }
+
+    pub fn bigram(&self, bigram: &str) -> Option<&KeyStat> {
+        self.bigrams.get(bigram)
+    }
+
+    /// Keys with the highest mean latency first.
+    pub fn slowest_keys(&self, count: usize) -> Vec<(char, KeyStat)> {
+        slowest(self.keys.iter().map(|(key, stat)| (*key, *stat)), count)
+    }
+
+    /// Bigrams with the highest mean latency first.
+    pub fn slowest_bigrams(&self, count: usize) -> Vec<(String, KeyStat)> {
+        slowest(
+            self.bigrams
+                .iter()
+                .map(|(bigram, stat)| (bigram.clone(), *stat)),
+            count,
+        )
+    }
+
+    /// Keys with the highest error rate first, ignoring keys never missed.
+    pub fn most_missed_keys(&self, count: usize) -> Vec<(char, KeyStat)> {
+        let mut keys = self
+            .keys
+            .iter()
+            .filter(|(_, stat)| stat.errors > 0)
+            .map(|(key, stat)| (*key, *stat))
+            .collect::<Vec<_>>();
+        keys.sort_by(|(_, a), (_, b)| b.error_rate().total_cmp(&a.error_rate()));
+        keys.truncate(count);
+        keys
+    }
+}
+
+fn slowest<K>(stats: impl Iterator<Item = (K, KeyStat)>, count: usize) -> Vec<(K, KeyStat)> {
+    let mut stats = stats
+        .filter(|(_, stat)| stat.timed_count > 0)
+        .collect::<Vec<_>>();
+    stats.sort_by_key(|(_, stat)| std::cmp::Reverse(stat.mean_latency()));
+    stats.truncate(count);
+    stats
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn should_attribute_intervals_to_keys_and_bigrams() {
+        let keystrokes = [
+            Keystroke::typed(0, 't', 't', 0),
+            Keystroke::typed(1, 'h', 'h', 200),
+            Keystroke::typed(2, 'e', 'x', 300),
+            Keystroke::Deleted {
+                position: 2,
+                at: Duration::from_millis(500),
+            },
+            Keystroke::typed(2, 'e', 'e', 600),
+        ];
+        let stats = TypingStats::compute("the", &keystrokes);
+
+        let t = stats.key('t').unwrap();
+        assert_eq!((t.count, t.timed_count), (1, 0));
+        assert_eq!(t.mean_latency(), None);
+
+        let e = stats.key('e').unwrap();
+        assert_eq!((e.count, e.errors), (2, 1));
+        assert_eq!(e.mean_latency(), Some(Duration::from_millis(100)));
+        assert_eq!(e.error_rate(), 0.5);
+
+        assert_eq!(
+            stats.bigram("th").unwrap().mean_latency(),
+            Some(Duration::from_millis(200))
+        );
+        assert_eq!(stats.bigram("he").unwrap().count, 2);
+        assert_eq!(stats.bigram("t"), None);
+    }
+
+    #[test]
+    fn should_rank_keys_and_bigrams() {
+        let keystrokes = [
+            Keystroke::typed(0, 'a', 'a', 0),
+            Keystroke::typed(1, 'b', 'x', 300),
+            Keystroke::typed(2, 'c', 'c', 400),
+        ];
+        let stats = TypingStats::compute("abc", &keystrokes);
+
+        let slowest = stats.slowest_keys(5);
+        assert_eq!(
+            slowest.iter().map(|(key, _)| *key).collect::<Vec<_>>(),
+            vec!['b', 'c']
+        );
+        assert_eq!(stats.slowest_bigrams(1)[0].0, "ab");
+        assert_eq!(stats.most_missed_keys(5)[0].0, 'b');
+    }
+
+    #[test]
+    fn should_merge_sessions() {
+        let mut stats = TypingStats::compute(
+            "ab",
+            &[
+                Keystroke::typed(0, 'a', 'a', 0),
+                Keystroke::typed(1, 'b', 'b', 100),
+            ],
+        );
+        stats.merge(&TypingStats::compute(
+            "ab",
+            &[
+                Keystroke::typed(0, 'a', 'x', 0),
+                Keystroke::typed(1, 'b', 'b', 300),
+            ],
+        ));
+
+        assert_eq!(stats.key('a').unwrap().errors, 1);
+        assert_eq!(
+            stats.bigram("ab").unwrap().mean_latency(),
+            Some(Duration::from_millis(200))
+        );
+    }
+
+    #[test]
+    fn should_serialize_to_json() {
+        let stats = TypingStats::compute(
+            "a b",
+            &[
+                Keystroke::typed(0, 'a', 'a', 0),
+                Keystroke::typed(1, ' ', ' ', 100),
+            ],
+        );
+        let json = serde_json::to_string(&stats).unwrap();
+
+        assert_eq!(serde_json::from_str::<TypingStats>(&json).unwrap(), stats);
+    }
+}
@@ -1,15 +1,34 @@
+use std::future::Future;
 use std::sync::atomic::{AtomicBool, Ordering};
 use std::sync::Arc;
+use std::thread;
 use std::time::Duration;
 
+use crossterm::event::Event;
 use log::error;
 
 use super::key::Key;
 use super::InputEvent;
 
+/// Failures in a row to poll the terminal after which it is given up, as it
+/// is most likely gone.
+const MAX_POLL_FAILURES: u32 = 10;
+
+/// Where the app reads its inputs from: the terminal, or a script in tests.
+pub trait EventSource {
+    /// Waits for the next input, or `None` once there are no more inputs to
+    /// read.
+    fn next(&mut self) -> impl Future<Output = Option<InputEvent>> + Send;
+
+    /// Stops reading inputs, once the app exits.
+    fn close(&mut self);
+}
+
+/// Keys and resizes read from the terminal, with a tick whenever none comes
+/// within the tick rate. The events end when the terminal cannot be read any
+/// more.
 pub struct Events {
     rx: tokio::sync::mpsc::Receiver<InputEvent>,
-    _tx: tokio::sync::mpsc::Sender<InputEvent>,
     stop_capture: Arc<AtomicBool>,
 }
 
@@ -18,39 +37,64 @@ impl Events {
         let (tx, rx) = tokio::sync::mpsc::channel(100);
         let stop_capture = Arc::new(AtomicBool::new(false));
 
-        let event_tx = tx.clone();
+        // The thread holds the only sender, so the channel closes when it
+        // stops.
+        let event_tx = tx;
         let event_stop_capture = stop_capture.clone();
-        tokio::spawn(async move {
-            loop {
-                if crossterm::event::poll(tick_rate).unwrap() {
-                    if let crossterm::event::Event::Key(key) = crossterm::event::read().unwrap() {
-                        let key = Key::from(key);
-                        if let Err(err) = event_tx.send(InputEvent::Input(key)).await {
+        // Polling crossterm blocks, so it runs on its own thread rather than
+        // holding one of the runtime workers.
+        let mut failures = 0;
+        thread::spawn(move || loop {
+            match crossterm::event::poll(tick_rate) {
+                Ok(true) => {
+                    failures = 0;
+                    let event = match crossterm::event::read() {
+                        Ok(Event::Key(key)) => Some(InputEvent::Input(Key::from(key))),
+                        Ok(Event::Resize(width, height)) => Some(InputEvent::Resize(width, height)),
+                        Ok(_) => None,
+                        Err(err) => {
+                            error!("Cannot read terminal event: {}", err);
+                            None
+                        }
+                    };
+                    if let Some(event) = event {
+                        if let Err(err) = event_tx.blocking_send(event) {
                             error!("Oops!, {}", err);
+                            break;
                         }
                     }
                 }
-                if let Err(err) = event_tx.send(InputEvent::Tick).await {
-                    error!("Oops!, {}", err);
-                }
-                if event_stop_capture.load(Ordering::Relaxed) {
-                    break;
+                Ok(false) => failures = 0,
+                Err(err) => {
+                    failures += 1;
+                    error!("Cannot poll terminal events: {}", err);
+                    if failures >= MAX_POLL_FAILURES {
+                        error!("Stop reading the terminal after {} failures", failures);
+                        break;
+                    }
+                    // Polling fails right away, wait as long as a tick.
+                    thread::sleep(tick_rate);
                 }
             }
+            if let Err(err) = event_tx.blocking_send(InputEvent::Tick) {
+                error!("Oops!, {}", err);
+                break;
+            }
+            if event_stop_capture.load(Ordering::Relaxed) {
+                break;
+            }
         });
 
-        Events {
-            rx,
-            _tx: tx,
-            stop_capture,
-        }
+        Events { rx, stop_capture }
     }
+}
 
-    pub async fn next(&mut self) -> InputEvent {
-        self.rx.recv().await.unwrap()
+impl EventSource for Events {
+    async fn next(&mut self) -> Option<InputEvent> {
+        self.rx.recv().await
     }
 
-    pub fn close(&mut self) {
+    fn close(&mut self) {
         self.stop_capture.store(true, Ordering::Relaxed)
     }
 }
@@ -1,4 +1,5 @@
 use std::fmt::{self, Display, Formatter};
+use std::str::FromStr;
 
 use crossterm::event;
 
@@ -61,6 +62,63 @@ impl Display for Key {
     }
 }
 
+impl FromStr for Key {
+    type Err = String;
+
+    /// Parses a key as displayed, such as `<Ctrl+r>`, or written by hand, such
+    /// as `ctrl-r`, `esc` or `f`. Brackets and case are optional.
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        let name = value.trim();
+        let name = name
+            .strip_prefix('<')
+            .and_then(|name| name.strip_suffix('>'))
+            .unwrap_or(name);
+        let lower = name.to_lowercase();
+
+        let modified = |prefix: &str| {
+            ["+", "-"].iter().find_map(|separator| {
+                lower
+                    .strip_prefix(&format!("{}{}", prefix, separator))
+                    .map(|_| &name[prefix.len() + 1..])
+            })
+        };
+        let single = |rest: &str| -> Option<char> {
+            let mut chars = rest.chars();
+            match (chars.next(), chars.next()) {
+                (Some(c), None) => Some(c),
+                _ if rest.eq_ignore_ascii_case("space") => Some(' '),
+                _ => None,
+            }
+        };
+
+        let key = if let Some(rest) = modified("ctrl") {
+            single(rest).map(|c| Key::Ctrl(c.to_ascii_lowercase()))
+        } else if let Some(rest) = modified("alt") {
+            single(rest).map(Key::Alt)
+        } else {
+            match lower.as_str() {
+                "enter" | "return" => Some(Key::Enter),
+                "tab" => Some(Key::Tab),
+                "backspace" => Some(Key::Backspace),
+                "esc" | "escape" => Some(Key::Esc),
+                "left" => Some(Key::Left),
+                "right" => Some(Key::Right),
+                "up" => Some(Key::Up),
+                "down" => Some(Key::Down),
+                "ins" | "insert" => Some(Key::Ins),
+                "delete" | "del" => Some(Key::Delete),
+                "home" => Some(Key::Home),
+                "end" => Some(Key::End),
+                "pageup" => Some(Key::PageUp),
+                "pagedown" => Some(Key::PageDown),
+                _ => single(name).map(Key::Char),
+            }
+        };
+
+        key.ok_or_else(|| format!("unknown key '{}'", value))
+    }
+}
+
 impl From<event::KeyEvent> for Key {
     fn from(key_event: event::KeyEvent) -> Self {
         match key_event {
@@ -136,3 +194,34 @@ impl From<event::KeyEvent> for Key {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn should_parse_keys() {
+        assert_eq!("<Ctrl+r>".parse(), Ok(Key::Ctrl('r')));
+        assert_eq!("ctrl-R".parse(), Ok(Key::Ctrl('r')));
+        assert_eq!("alt+space".parse(), Ok(Key::Alt(' ')));
+        assert_eq!("Esc".parse(), Ok(Key::Esc));
+        assert_eq!("<PageDown>".parse(), Ok(Key::PageDown));
+        assert_eq!("q".parse(), Ok(Key::Char('q')));
+        assert!("ctrl+".parse::<Key>().is_err());
+        assert!("hyper+x".parse::<Key>().is_err());
+    }
+
+    #[test]
+    fn should_parse_displayed_keys() {
+        for key in [
+            Key::Enter,
+            Key::Tab,
+            Key::Ins,
+            Key::Char(' '),
+            Key::Ctrl('p'),
+            Key::Alt('x'),
+        ] {
+            assert_eq!(key.to_string().parse(), Ok(key));
+        }
+    }
+}
@@ -8,4 +8,6 @@ pub enum InputEvent {
     Input(Key),
     /// An tick event occurred.
     Tick,
+    /// The terminal was resized to the given width and height.
+    Resize(u16, u16),
 }
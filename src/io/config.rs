@@ -0,0 +1,95 @@
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use eyre::{Result, WrapErr};
+
+use crate::app::config::Config;
+
+const CONFIG_FILE: &str = "config.toml";
+
+/// Directory where jackdull reads its settings, under the XDG config
+/// directory.
+pub fn config_dir() -> PathBuf {
+    dirs::config_dir()
+        .unwrap_or_else(|| PathBuf::from("."))
+        .join("jackdull")
+}
+
+/// The TOML config file, read at startup and written by the settings screen.
+#[derive(Debug, Clone)]
+pub struct ConfigStore {
+    path: PathBuf,
+}
+
+impl ConfigStore {
+    pub fn new(path: impl Into<PathBuf>) -> Self {
+        Self { path: path.into() }
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// The config in the file, or the default one when there is no file.
+    pub fn load(&self) -> Result<Config> {
+        if !self.path.exists() {
+            return Ok(Config::default());
+        }
+
+        let content = fs::read_to_string(&self.path)
+            .wrap_err_with(|| format!("error reading {}", self.path.display()))?;
+        Config::from_toml(&content)
+            .wrap_err_with(|| format!("invalid config in {}", self.path.display()))
+    }
+
+    pub fn save(&self, config: &Config) -> Result<()> {
+        if let Some(parent) = self.path.parent() {
+            fs::create_dir_all(parent)
+                .wrap_err_with(|| format!("error creating directory {}", parent.display()))?;
+        }
+
+        fs::write(&self.path, config.to_toml()?)
+            .wrap_err_with(|| format!("error writing {}", self.path.display()))
+    }
+}
+
+impl Default for ConfigStore {
+    fn default() -> Self {
+        Self::new(config_dir().join(CONFIG_FILE))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::engine::mode::TestMode;
+
+    #[test]
+    fn should_save_and_load_config() {
+        let dir = tempfile::tempdir().unwrap();
+        let store = ConfigStore::new(dir.path().join("nested").join(CONFIG_FILE));
+        assert_eq!(store.load().unwrap(), Config::default());
+
+        let config = Config {
+            mode: TestMode::Words(25),
+            ..Config::default()
+        };
+        store.save(&config).unwrap();
+
+        assert_eq!(store.load().unwrap(), config);
+    }
+
+    #[test]
+    fn should_name_the_file_in_errors() {
+        let dir = tempfile::tempdir().unwrap();
+        let store = ConfigStore::new(dir.path().join(CONFIG_FILE));
+        fs::write(store.path(), "tick_rate_ms = 0").unwrap();
+
+        let error = store.load().unwrap_err();
+        assert!(error.to_string().starts_with("invalid config in "));
+        assert_eq!(
+            error.root_cause().to_string(),
+            "tick_rate_ms must be between 10 and 1000, got 0"
+        );
+    }
+}
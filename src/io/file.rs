@@ -1,7 +1,10 @@
+use std::fmt::{self, Display};
+use std::path::Path;
+use std::{fs, io};
+
 use serde::{Deserialize, Serialize};
-use std::fs;
 
-#[derive(Serialize, Deserialize, Debug)]
+#[derive(Serialize, Deserialize, Debug, Clone)]
 pub struct TypingFile {
     pub from: String,
     pub content: String,
@@ -10,23 +13,158 @@ pub struct TypingFile {
 
 #[derive(Debug, Clone)]
 pub struct TypingFileDisplay {
+    pub id: String,
     pub from: String,
     pub content: String,
     pub url: String,
     pub words_count: u32,
 }
 
-pub async fn read_file() -> TypingFileDisplay {
-    let value = {
-        let file_content =
-            fs::read_to_string("./texts/1.json").expect("LogRocket: error reading file");
-        serde_json::from_str::<TypingFile>(&file_content).expect("error serializing to JSON")
+impl TypingFileDisplay {
+    pub fn new(id: String, value: TypingFile) -> Self {
+        let words_count = value.content.split_whitespace().count() as u32;
+
+        Self {
+            id,
+            from: value.from,
+            content: value.content,
+            url: value.url,
+            words_count,
+        }
+    }
+}
+
+/// Everything that can go wrong while loading a text to type.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum TextError {
+    NotFound { path: String },
+    Unreadable { path: String, reason: String },
+    InvalidJson { id: String, reason: String },
+    EmptyContent { id: String },
+    InvalidEncoding { path: String },
+}
+
+impl Display for TextError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            TextError::NotFound { path } => write!(f, "No text found at {}", path),
+            TextError::Unreadable { path, reason } => {
+                write!(f, "Cannot read {}: {}", path, reason)
+            }
+            TextError::InvalidJson { id, reason } => {
+                write!(f, "Text {} is not a valid text file: {}", id, reason)
+            }
+            TextError::EmptyContent { id } => write!(f, "Text {} has no content to type", id),
+            TextError::InvalidEncoding { path } => write!(f, "{} is not valid UTF-8", path),
+        }
+    }
+}
+
+impl std::error::Error for TextError {}
+
+pub fn parse_file(id: &str, file_content: &str) -> Result<TypingFileDisplay, TextError> {
+    let value =
+        serde_json::from_str::<TypingFile>(file_content).map_err(|err| TextError::InvalidJson {
+            id: id.to_owned(),
+            reason: err.to_string(),
+        })?;
+
+    if value.content.trim().is_empty() {
+        return Err(TextError::EmptyContent { id: id.to_owned() });
+    }
+
+    Ok(TypingFileDisplay::new(id.to_owned(), value))
+}
+
+/// Reads a JSON text file, or any other file as plain text credited to its
+/// file name. The id of the text is the file stem.
+pub fn read_file(path: &Path) -> Result<TypingFileDisplay, TextError> {
+    let id = path
+        .file_stem()
+        .map(|stem| stem.to_string_lossy().into_owned())
+        .unwrap_or_default();
+    let file_content = fs::read_to_string(path).map_err(|err| {
+        let path = path.display().to_string();
+        match err.kind() {
+            io::ErrorKind::NotFound => TextError::NotFound { path },
+            io::ErrorKind::InvalidData => TextError::InvalidEncoding { path },
+            _ => TextError::Unreadable {
+                path,
+                reason: err.to_string(),
+            },
+        }
+    })?;
+
+    if path.extension().is_some_and(|ext| ext == "json") {
+        return parse_file(&id, &file_content);
+    }
+    if file_content.trim().is_empty() {
+        return Err(TextError::EmptyContent { id });
+    }
+    let from = path
+        .file_name()
+        .map(|name| name.to_string_lossy().into_owned())
+        .unwrap_or_default();
+    let value = TypingFile {
+        from,
+        content: file_content,
+        url: "".to_owned(),
     };
 
-    TypingFileDisplay {
-        from: value.from,
-        content: value.content.clone(),
-        url: value.url,
-        words_count: value.content.split_whitespace().count() as u32,
+    Ok(TypingFileDisplay::new(id, value))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn should_parse_text() {
+        let text = parse_file("1", r#"{"from": "a", "content": "b c", "url": "d"}"#).unwrap();
+        assert_eq!(text.id, "1");
+        assert_eq!(text.words_count, 2);
+    }
+
+    #[test]
+    fn should_fail_on_bad_json() {
+        let result = parse_file("1", "{");
+        assert!(matches!(result, Err(TextError::InvalidJson { .. })));
+    }
+
+    #[test]
+    fn should_fail_on_empty_content() {
+        let result = parse_file("1", r#"{"from": "a", "content": "  ", "url": "d"}"#);
+        assert_eq!(
+            result.unwrap_err(),
+            TextError::EmptyContent { id: "1".to_owned() }
+        );
+    }
+
+    #[test]
+    fn should_fail_on_missing_file() {
+        let result = read_file(Path::new("./texts/does-not-exist.json"));
+        assert!(matches!(result, Err(TextError::NotFound { .. })));
+    }
+
+    #[test]
+    fn should_read_plain_text() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("drill.txt");
+        fs::write(&path, "the quick brown fox\n").unwrap();
+
+        let text = read_file(&path).unwrap();
+        assert_eq!(text.id, "drill");
+        assert_eq!(text.from, "drill.txt");
+        assert_eq!(text.words_count, 4);
+    }
+
+    #[test]
+    fn should_fail_on_invalid_encoding() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("latin1.json");
+        fs::write(&path, [0xff, 0xfe, 0x00]).unwrap();
+
+        let result = read_file(&path);
+        assert!(matches!(result, Err(TextError::InvalidEncoding { .. })));
     }
 }
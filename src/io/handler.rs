@@ -1,48 +1,102 @@
 use std::sync::Arc;
 use std::time::Duration;
 
-use eyre::Result;
-use log::{error, info};
+use log::{error, info, warn};
 
 use super::IoEvent;
-use crate::{app::App, io::file::read_file};
+use crate::app::ghost::{Ghost, GhostRun};
+use crate::app::replay::Replay;
+use crate::app::App;
+use crate::io::config::ConfigStore;
+use crate::io::file::{TextError, TypingFileDisplay};
+use crate::io::history::{HistoryStore, SessionRecord};
+use crate::io::library::TextLibrary;
+use crate::io::replays::ReplayStore;
+use crate::io::stats::StatsStore;
+use crate::io::themes::ThemeStore;
 
 pub struct IoAsyncHandler {
     app: Arc<tokio::sync::Mutex<App>>,
+    library: TextLibrary,
+    history: HistoryStore,
+    stats: StatsStore,
+    themes: ThemeStore,
+    config: ConfigStore,
+    replays: ReplayStore,
 }
 
 impl IoAsyncHandler {
-    pub fn new(app: Arc<tokio::sync::Mutex<App>>) -> Self {
-        Self { app }
+    pub fn new(
+        app: Arc<tokio::sync::Mutex<App>>,
+        library: TextLibrary,
+        history: HistoryStore,
+        stats: StatsStore,
+        themes: ThemeStore,
+        config: ConfigStore,
+        replays: ReplayStore,
+    ) -> Self {
+        Self {
+            app,
+            library,
+            history,
+            stats,
+            themes,
+            config,
+            replays,
+        }
     }
 
-    pub async fn handle_io_event(&mut self, io_event: IoEvent) {
+    pub async fn handle_io_event(&mut self, io_event: IoEvent) -> Result<(), TextError> {
         let result = match io_event {
             IoEvent::Initialize => self.do_initialize().await,
-            IoEvent::Timer => self.timer().await,
-            IoEvent::TimeUp => self.timeup().await,
-            IoEvent::FinishText => self.finished_text().await,
+            IoEvent::StartTest => self.start_test().await,
+            IoEvent::NextText => self.next_text().await,
+            IoEvent::OpenHistory => self.open_history().await,
+            IoEvent::SaveConfig => self.save_config().await,
+            IoEvent::SaveSession => self.save_session().await,
         };
 
-        if let Err(err) = result {
+        if let Err(err) = &result {
             error!("Oops, something wrong happen: {:?}", err);
+            let mut app = self.app.lock().await;
+            app.failed(err.clone());
         }
+
+        result
     }
 
-    async fn do_initialize(&mut self) -> Result<()> {
+    async fn do_initialize(&mut self) -> Result<(), TextError> {
         info!("🚀 Initialize the application");
 
         let mut app = self.app.lock().await;
         app.loading();
 
         tokio::time::sleep(Duration::from_secs(1)).await;
-        app.initialized();
 
-        let data_from_file = read_file().await;
-        app.set_typing_information(data_from_file.clone());
+        self.library.load()?;
+        app.set_texts(self.library.list());
+        match self.stats.load() {
+            Ok(stats) => app.set_overall_stats(stats),
+            Err(err) => error!("Cannot load the key statistics: {:?}", err),
+        }
+        match self.themes.load() {
+            Ok(themes) => app.add_themes(themes),
+            Err(err) => error!("Cannot load the custom themes: {:?}", err),
+        }
+        let theme = app.settings().theme.clone();
+        if !app.select_theme(&theme) {
+            warn!("Unknown theme '{}', the default one is used", theme);
+        }
 
-        app.load_text(data_from_file.content);
-        app.set_words_count(data_from_file.words_count);
+        // A replay given on the command line is already in place.
+        if !app.is_replaying() {
+            if app.skip_menu() {
+                let text = self.pick_text(&app)?;
+                self.prepare_test(&mut app, text);
+            } else {
+                app.open_menu();
+            }
+        }
 
         info!("👍 Application initialized");
 
@@ -51,35 +105,172 @@ impl IoAsyncHandler {
         Ok(())
     }
 
-    async fn timer(&mut self) -> Result<()> {
+    async fn start_test(&mut self) -> Result<(), TextError> {
         let mut app = self.app.lock().await;
-        app.update_on_tick().await;
-        info!("Increase timer");
+        let text = self.pick_text(&app)?;
+        self.prepare_test(&mut app, text);
+        info!("🏁 Test started in {} mode", app.mode());
 
         Ok(())
     }
 
-    async fn timeup(&mut self) -> Result<()> {
+    async fn next_text(&mut self) -> Result<(), TextError> {
         let mut app = self.app.lock().await;
-        app.send_message_timeup().await;
-        info!("Time is up");
+        let current = app
+            .state()
+            .typing_information()
+            .map(|text| text.id)
+            .unwrap_or_default();
+        let text = self
+            .library
+            .next(&current)
+            .cloned()
+            .ok_or_else(|| self.not_found(None))?;
+        self.prepare_test(&mut app, text);
+        info!("⏭ Next text started in {} mode", app.mode());
 
         Ok(())
     }
 
-    async fn finished_text(&mut self) -> Result<()> {
+    async fn open_history(&mut self) -> Result<(), TextError> {
         let mut app = self.app.lock().await;
-        app.finished_text().await;
-        info!("Finished text");
+        match self.history.load() {
+            Ok(history) => {
+                info!("📈 {} sessions loaded", history.records().len());
+                app.show_history(history);
+            }
+            Err(err) => error!("Cannot load the history: {:?}", err),
+        }
 
         Ok(())
     }
 
-    async fn process_the_typing(&mut self) -> Result<()> {
+    async fn save_config(&mut self) -> Result<(), TextError> {
+        let mut app = self.app.lock().await;
+        let Some(config) = app.state().settings().map(|view| view.config().clone()) else {
+            return Ok(());
+        };
+
+        match self.config.save(&config) {
+            Ok(()) => {
+                info!("⚙ Settings saved to {}", self.config.path().display());
+                if config.tick_rate_ms != app.settings().tick_rate_ms {
+                    info!("⚙ Restart to tick every {} ms", config.tick_rate_ms);
+                }
+                app.config_saved(config);
+            }
+            Err(err) => error!("Cannot save the settings: {:?}", err),
+        }
+
+        Ok(())
+    }
+
+    fn pick_text(&self, app: &App) -> Result<TypingFileDisplay, TextError> {
+        match app.text_id() {
+            Some(id) => self.library.get(id),
+            None => self.library.random(),
+        }
+        .cloned()
+        .ok_or_else(|| self.not_found(app.text_id()))
+    }
+
+    fn not_found(&self, id: Option<&str>) -> TextError {
+        let dir = self.library.dir().display();
+        TextError::NotFound {
+            path: match id {
+                Some(id) => format!("{} in {}", id, dir),
+                None => dir.to_string(),
+            },
+        }
+    }
+
+    fn prepare_test(&self, app: &mut App, text: TypingFileDisplay) {
+        info!("📖 Text {} picked from the library", text.id);
+
+        // The race is on the text of the earlier run, since word-count tests
+        // pad short texts with random ones.
+        let ghost = self.load_ghost(app, &text.id);
+        let to_type = match (&ghost, app.mode().word_goal()) {
+            (Some(ghost), _) => ghost.text.clone(),
+            (None, Some(goal)) => self.library.words(&text, goal),
+            (None, None) => text.content.clone(),
+        };
+
+        app.initialized();
+        app.set_typing_information(text);
+
+        app.set_words_count(to_type.split_whitespace().count() as u32);
+        app.load_text(to_type);
+        if let Some(ghost) = ghost {
+            app.set_ghost(ghost);
+        }
+    }
+
+    /// The earlier run of the text picked in the settings, if any.
+    fn load_ghost(&self, app: &App, text_id: &str) -> Option<Ghost> {
+        let run = app.settings().ghost;
+        if run == GhostRun::Off {
+            return None;
+        }
+
+        let replays = match self.replays.find(text_id, app.mode()) {
+            Ok(replays) => replays,
+            Err(err) => {
+                error!("Cannot load the replays: {:?}", err);
+                return None;
+            }
+        };
+        match run.pick(&replays) {
+            Some(replay) => {
+                info!(
+                    "👻 Racing the {} run of {} at {:.0} WPM",
+                    run, text_id, replay.wpm
+                );
+                Some(Ghost::new(run, replay))
+            }
+            None => {
+                info!(
+                    "👻 No earlier run of {} in {} mode to race",
+                    text_id,
+                    app.mode()
+                );
+                None
+            }
+        }
+    }
+
+    async fn save_session(&mut self) -> Result<(), TextError> {
         let mut app = self.app.lock().await;
-        let to_type = app.state().to_type();
-        if let Some(to_type) = to_type {
-            app.state().typed_text();
+        let (Some(text), Some(mode), Some(result)) = (
+            app.state().typing_information(),
+            app.state().mode(),
+            app.state().result(),
+        ) else {
+            return Ok(());
+        };
+
+        let record = SessionRecord::new(&text, mode, result);
+        match self.history.append(&record) {
+            Ok(()) => info!("💾 Session saved to {}", self.history.path().display()),
+            Err(err) => error!("Cannot save the session: {:?}", err),
+        }
+
+        if let Some(replay) = app
+            .state()
+            .engine()
+            .and_then(|engine| Replay::record(&text, engine))
+        {
+            match self.replays.save(&replay) {
+                Ok(path) => info!("🎞 Replay saved to {}", path.display()),
+                Err(err) => error!("Cannot save the replay: {:?}", err),
+            }
+        }
+
+        if let Some(stats) = app.state().typing_stats() {
+            match self.stats.add(&stats) {
+                Ok(overall) => app.set_overall_stats(overall),
+                Err(err) => error!("Cannot save the key statistics: {:?}", err),
+            }
         }
 
         Ok(())
@@ -0,0 +1,303 @@
+use std::fs::{self, OpenOptions};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+
+use chrono::{DateTime, Utc};
+use eyre::{Result, WrapErr};
+use log::warn;
+use serde::{Deserialize, Serialize};
+
+use crate::engine::mode::TestMode;
+use crate::engine::score::{EndReason, TestResult};
+use crate::io::file::TypingFileDisplay;
+
+const HISTORY_FILE: &str = "history.jsonl";
+
+/// Directory where jackdull keeps its data, under the XDG data directory.
+pub fn data_dir() -> PathBuf {
+    dirs::data_dir()
+        .unwrap_or_else(|| PathBuf::from("."))
+        .join("jackdull")
+}
+
+/// The result of a finished or timed-out test, as stored in the history.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct SessionRecord {
+    pub timestamp: DateTime<Utc>,
+    pub passage_id: String,
+    pub from: String,
+    pub url: String,
+    pub mode: TestMode,
+    pub reason: EndReason,
+    pub wpm: f64,
+    pub raw_wpm: f64,
+    pub cpm: f64,
+    pub accuracy: f64,
+    pub duration_ms: u64,
+    pub total_keystrokes: usize,
+    pub correct_keystrokes: usize,
+    pub corrected_errors: usize,
+    pub uncorrected_errors: usize,
+}
+
+impl SessionRecord {
+    pub fn new(text: &TypingFileDisplay, mode: TestMode, result: &TestResult) -> Self {
+        let score = &result.score;
+
+        Self {
+            timestamp: Utc::now(),
+            passage_id: text.id.clone(),
+            from: text.from.clone(),
+            url: text.url.clone(),
+            mode,
+            reason: result.reason,
+            wpm: score.net_wpm,
+            raw_wpm: score.gross_wpm,
+            cpm: score.cpm,
+            accuracy: score.accuracy.percentage,
+            duration_ms: score.elapsed.as_millis() as u64,
+            total_keystrokes: score.accuracy.total_keystrokes,
+            correct_keystrokes: score.accuracy.correct_keystrokes,
+            corrected_errors: score.accuracy.corrected_errors,
+            uncorrected_errors: score.accuracy.uncorrected_errors,
+        }
+    }
+
+    /// A flawless one-second session, for tests.
+    #[cfg(test)]
+    pub fn sample(passage_id: &str, mode: TestMode, wpm: f64) -> Self {
+        Self {
+            timestamp: Utc::now(),
+            passage_id: passage_id.to_owned(),
+            from: format!("Author {}", passage_id),
+            url: "".to_owned(),
+            mode,
+            reason: EndReason::Finished,
+            wpm,
+            raw_wpm: wpm,
+            cpm: wpm * 5.0,
+            accuracy: 100.0,
+            duration_ms: 1000,
+            total_keystrokes: 10,
+            correct_keystrokes: 10,
+            corrected_errors: 0,
+            uncorrected_errors: 0,
+        }
+    }
+}
+
+/// Appends each session as one JSON line to the history file.
+#[derive(Debug, Clone)]
+pub struct HistoryStore {
+    path: PathBuf,
+}
+
+impl HistoryStore {
+    pub fn new(path: impl Into<PathBuf>) -> Self {
+        Self { path: path.into() }
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    pub fn append(&self, record: &SessionRecord) -> Result<()> {
+        if let Some(parent) = self.path.parent() {
+            fs::create_dir_all(parent)
+                .wrap_err_with(|| format!("error creating directory {}", parent.display()))?;
+        }
+
+        let mut file = OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&self.path)
+            .wrap_err_with(|| format!("error opening {}", self.path.display()))?;
+        let line = serde_json::to_string(record)?;
+        writeln!(file, "{}", line)
+            .wrap_err_with(|| format!("error writing {}", self.path.display()))?;
+
+        Ok(())
+    }
+
+    /// Every stored session, oldest first. Lines that cannot be read are
+    /// skipped.
+    pub fn load(&self) -> Result<History> {
+        if !self.path.exists() {
+            return Ok(History::default());
+        }
+
+        let content = fs::read_to_string(&self.path)
+            .wrap_err_with(|| format!("error reading {}", self.path.display()))?;
+        let records = content
+            .lines()
+            .enumerate()
+            .filter(|(_, line)| !line.trim().is_empty())
+            .filter_map(|(index, line)| match serde_json::from_str(line) {
+                Ok(record) => Some(record),
+                Err(err) => {
+                    warn!("Skip history line {}: {}", index + 1, err);
+                    None
+                }
+            })
+            .collect();
+
+        Ok(History { records })
+    }
+}
+
+impl Default for HistoryStore {
+    fn default() -> Self {
+        Self::new(data_dir().join(HISTORY_FILE))
+    }
+}
+
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct History {
+    records: Vec<SessionRecord>,
+}
+
+impl History {
+    pub fn records(&self) -> &[SessionRecord] {
+        self.records.as_slice()
+    }
+
+    pub fn query(&self) -> HistoryQuery<'_> {
+        HistoryQuery {
+            records: &self.records,
+            mode: None,
+            passage_id: None,
+        }
+    }
+}
+
+impl From<Vec<SessionRecord>> for History {
+    fn from(records: Vec<SessionRecord>) -> Self {
+        Self { records }
+    }
+}
+
+/// Filters the history, then aggregates the matching sessions.
+#[derive(Debug, Clone)]
+pub struct HistoryQuery<'a> {
+    records: &'a [SessionRecord],
+    mode: Option<TestMode>,
+    passage_id: Option<String>,
+}
+
+impl<'a> HistoryQuery<'a> {
+    pub fn mode(mut self, mode: TestMode) -> Self {
+        self.mode = Some(mode);
+        self
+    }
+
+    pub fn passage(mut self, passage_id: impl Into<String>) -> Self {
+        self.passage_id = Some(passage_id.into());
+        self
+    }
+
+    /// Matching sessions, oldest first.
+    pub fn records(&self) -> Vec<&'a SessionRecord> {
+        self.records
+            .iter()
+            .filter(|record| self.mode.is_none_or(|mode| record.mode == mode))
+            .filter(|record| {
+                self.passage_id
+                    .as_ref()
+                    .is_none_or(|id| record.passage_id == *id)
+            })
+            .collect()
+    }
+
+    /// The `count` most recent matching sessions, oldest first.
+    pub fn last(&self, count: usize) -> Vec<&'a SessionRecord> {
+        let records = self.records();
+        let skip = records.len().saturating_sub(count);
+        records.into_iter().skip(skip).collect()
+    }
+
+    pub fn best(&self) -> Option<&'a SessionRecord> {
+        self.records()
+            .into_iter()
+            .max_by(|a, b| a.wpm.total_cmp(&b.wpm))
+    }
+
+    /// Mean WPM of the `count` most recent matching sessions.
+    pub fn average_wpm(&self, count: usize) -> Option<f64> {
+        average(self.last(count).iter().map(|record| record.wpm))
+    }
+
+    /// Mean accuracy of the `count` most recent matching sessions.
+    pub fn average_accuracy(&self, count: usize) -> Option<f64> {
+        average(self.last(count).iter().map(|record| record.accuracy))
+    }
+}
+
+fn average(values: impl Iterator<Item = f64>) -> Option<f64> {
+    let (sum, count) = values.fold((0.0, 0), |(sum, count), value| (sum + value, count + 1));
+    if count > 0 {
+        Some(sum / count as f64)
+    } else {
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use super::*;
+
+    #[test]
+    fn should_append_and_load_records() {
+        let dir = tempfile::tempdir().unwrap();
+        let store = HistoryStore::new(dir.path().join("nested").join(HISTORY_FILE));
+        assert!(store.load().unwrap().records().is_empty());
+
+        let first = SessionRecord::sample("1", TestMode::Passage, 40.0);
+        let second = SessionRecord::sample("2", TestMode::Words(10), 50.0);
+        store.append(&first).unwrap();
+        store.append(&second).unwrap();
+
+        let history = store.load().unwrap();
+        assert_eq!(history.records(), &[first, second]);
+    }
+
+    #[test]
+    fn should_skip_unreadable_lines() {
+        let dir = tempfile::tempdir().unwrap();
+        let store = HistoryStore::new(dir.path().join(HISTORY_FILE));
+        store
+            .append(&SessionRecord::sample("1", TestMode::Passage, 40.0))
+            .unwrap();
+        fs::write(
+            store.path(),
+            fs::read_to_string(store.path()).unwrap() + "not json\n",
+        )
+        .unwrap();
+
+        assert_eq!(store.load().unwrap().records().len(), 1);
+    }
+
+    #[test]
+    fn should_query_history() {
+        let timed = TestMode::Timed(Duration::from_secs(30));
+        let history = History::from(vec![
+            SessionRecord::sample("1", timed, 40.0),
+            SessionRecord::sample("2", timed, 60.0),
+            SessionRecord::sample("1", TestMode::Passage, 80.0),
+            SessionRecord::sample("1", timed, 50.0),
+        ]);
+
+        let query = history.query().mode(timed);
+        assert_eq!(query.records().len(), 3);
+        assert_eq!(query.best().unwrap().wpm, 60.0);
+        assert_eq!(query.average_wpm(2), Some(55.0));
+        assert_eq!(query.average_wpm(100), Some(50.0));
+
+        let query = history.query().passage("1");
+        assert_eq!(query.records().len(), 3);
+        assert_eq!(query.best().unwrap().wpm, 80.0);
+
+        assert_eq!(history.query().passage("3").average_wpm(10), None);
+    }
+}
@@ -0,0 +1,264 @@
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use log::{debug, warn};
+use rand::seq::SliceRandom;
+
+use crate::io::file::{parse_file, read_file, TextError, TypingFileDisplay};
+
+pub const DEFAULT_TEXTS_DIR: &str = "./texts";
+
+/// Texts compiled into the binary, so there is always something to type.
+const BUNDLED_TEXTS: [(&str, &str); 1] = [("1", include_str!("../../texts/1.json"))];
+
+/// Every passage available to the player: the bundled set, plus any `*.json`
+/// file found in the texts directory, plus the files added one by one. A
+/// file replaces a bundled text with the same id (its file stem).
+#[derive(Debug, Clone)]
+pub struct TextLibrary {
+    dir: PathBuf,
+    files: Vec<TypingFileDisplay>,
+    texts: Vec<TypingFileDisplay>,
+}
+
+impl TextLibrary {
+    pub fn new(dir: impl Into<PathBuf>) -> Self {
+        Self {
+            dir: dir.into(),
+            files: vec![],
+            texts: vec![],
+        }
+    }
+
+    /// Adds a text file from outside the texts directory, and returns its
+    /// id.
+    pub fn add_file(&mut self, path: &Path) -> Result<String, TextError> {
+        let text = read_file(path)?;
+        let id = text.id.clone();
+        self.files.retain(|known| known.id != id);
+        self.files.push(text);
+        Ok(id)
+    }
+
+    pub fn dir(&self) -> &PathBuf {
+        &self.dir
+    }
+
+    /// Loads the bundled texts and the texts of the directory. A file of the
+    /// directory that cannot be read is skipped with a warning.
+    pub fn load(&mut self) -> Result<(), TextError> {
+        let mut texts = BUNDLED_TEXTS
+            .iter()
+            .map(|(id, content)| parse_file(id, content))
+            .collect::<Result<Vec<_>, _>>()?;
+
+        for text in self.read_dir()?.into_iter().chain(self.files.clone()) {
+            match texts.iter_mut().find(|known| known.id == text.id) {
+                Some(known) => *known = text,
+                None => texts.push(text),
+            }
+        }
+
+        texts.sort_by(|a, b| a.id.cmp(&b.id));
+        debug!("Loaded {} texts", texts.len());
+        self.texts = texts;
+
+        Ok(())
+    }
+
+    /// The texts of the directory, without the files that cannot be read.
+    fn read_dir(&self) -> Result<Vec<TypingFileDisplay>, TextError> {
+        if !self.dir.is_dir() {
+            warn!("Texts directory {} not found", self.dir.display());
+            return Ok(vec![]);
+        }
+
+        let unreadable = |err: std::io::Error| TextError::Unreadable {
+            path: self.dir.display().to_string(),
+            reason: err.to_string(),
+        };
+        let entries = fs::read_dir(&self.dir).map_err(unreadable)?;
+
+        let mut texts = vec![];
+        for entry in entries {
+            let path = entry.map_err(unreadable)?.path();
+            if path.extension().is_some_and(|ext| ext == "json") {
+                match read_file(&path) {
+                    Ok(text) => texts.push(text),
+                    Err(err) => warn!("Skip text {}: {}", path.display(), err),
+                }
+            }
+        }
+
+        Ok(texts)
+    }
+
+    pub fn list(&self) -> &[TypingFileDisplay] {
+        self.texts.as_slice()
+    }
+
+    pub fn get(&self, id: &str) -> Option<&TypingFileDisplay> {
+        self.texts.iter().find(|text| text.id == id)
+    }
+
+    /// The text following `id`, back to the first one after the last.
+    pub fn next(&self, id: &str) -> Option<&TypingFileDisplay> {
+        let next = self
+            .texts
+            .iter()
+            .position(|text| text.id == id)
+            .map_or(0, |index| index + 1);
+        self.texts.get(next).or_else(|| self.texts.first())
+    }
+
+    pub fn random(&self) -> Option<&TypingFileDisplay> {
+        self.texts.choose(&mut rand::thread_rng())
+    }
+
+    /// Builds a text of `goal` words, starting with `first` and going on
+    /// with random texts of the library when `first` is too short.
+    pub fn words(&self, first: &TypingFileDisplay, goal: usize) -> String {
+        let mut words = first.content.split_whitespace().collect::<Vec<_>>();
+        let mut rng = rand::thread_rng();
+
+        while words.len() < goal {
+            match self.texts.choose(&mut rng) {
+                Some(text) if !text.content.trim().is_empty() => {
+                    words.extend(text.content.split_whitespace())
+                }
+                _ => break,
+            }
+        }
+
+        words.truncate(goal);
+        words.join(" ")
+    }
+}
+
+impl Default for TextLibrary {
+    fn default() -> Self {
+        Self::new(DEFAULT_TEXTS_DIR)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs;
+
+    use super::*;
+
+    fn texts_dir(files: &[(&str, &str)]) -> tempfile::TempDir {
+        let dir = tempfile::tempdir().unwrap();
+        for (name, content) in files {
+            fs::write(dir.path().join(name), content).unwrap();
+        }
+        dir
+    }
+
+    #[test]
+    fn should_load_bundled_texts_without_directory() {
+        let mut library = TextLibrary::new("./does-not-exist");
+        library.load().unwrap();
+
+        assert_eq!(library.list().len(), BUNDLED_TEXTS.len());
+        assert!(library.get("1").is_some());
+    }
+
+    #[test]
+    fn should_load_texts_from_directory() {
+        let dir = texts_dir(&[
+            (
+                "2.json",
+                r#"{"from": "Someone", "content": "one two three", "url": ""}"#,
+            ),
+            ("notes.txt", "not a text"),
+        ]);
+        let mut library = TextLibrary::new(dir.path());
+        library.load().unwrap();
+
+        let text = library.get("2").unwrap();
+        assert_eq!(text.from, "Someone");
+        assert_eq!(text.words_count, 3);
+        assert_eq!(library.list().len(), BUNDLED_TEXTS.len() + 1);
+    }
+
+    #[test]
+    fn should_override_bundled_text_with_same_id() {
+        let dir = texts_dir(&[(
+            "1.json",
+            r#"{"from": "Override", "content": "hello", "url": ""}"#,
+        )]);
+        let mut library = TextLibrary::new(dir.path());
+        library.load().unwrap();
+
+        assert_eq!(library.get("1").unwrap().from, "Override");
+        assert_eq!(library.list().len(), BUNDLED_TEXTS.len());
+    }
+
+    #[test]
+    fn should_keep_added_files() {
+        let dir = texts_dir(&[("drill.txt", "asdf jkl;")]);
+        let mut library = TextLibrary::new("./does-not-exist");
+
+        let id = library.add_file(&dir.path().join("drill.txt")).unwrap();
+        library.load().unwrap();
+
+        assert_eq!(id, "drill");
+        assert_eq!(library.get("drill").unwrap().content, "asdf jkl;");
+        assert!(library.add_file(&dir.path().join("missing.txt")).is_err());
+    }
+
+    #[test]
+    fn should_skip_malformed_text_in_directory() {
+        let dir = texts_dir(&[
+            ("broken.json", r#"{"from": "Someone""#),
+            (
+                "2.json",
+                r#"{"from": "Someone", "content": "hello", "url": ""}"#,
+            ),
+        ]);
+        let mut library = TextLibrary::new(dir.path());
+        library.load().unwrap();
+
+        assert!(library.get("broken").is_none());
+        assert!(library.get("2").is_some());
+        assert_eq!(library.list().len(), BUNDLED_TEXTS.len() + 1);
+    }
+
+    #[test]
+    fn should_pick_next_text() {
+        let dir = texts_dir(&[(
+            "2.json",
+            r#"{"from": "Someone", "content": "hello", "url": ""}"#,
+        )]);
+        let mut library = TextLibrary::new(dir.path());
+        library.load().unwrap();
+
+        assert_eq!(library.next("1").unwrap().id, "2");
+        assert_eq!(library.next("2").unwrap().id, "1");
+        assert_eq!(library.next("unknown").unwrap().id, "1");
+    }
+
+    #[test]
+    fn should_build_text_with_word_goal() {
+        let mut library = TextLibrary::new("./does-not-exist");
+        library.load().unwrap();
+        let first = library.get("1").unwrap();
+
+        let short = library.words(first, 3);
+        assert_eq!(short, "But why was");
+
+        let long = library.words(first, 100);
+        assert_eq!(long.split_whitespace().count(), 100);
+        assert!(!long.contains('\n'));
+    }
+
+    #[test]
+    fn should_pick_random_text() {
+        let mut library = TextLibrary::default();
+        assert!(library.random().is_none());
+
+        library.load().unwrap();
+        assert!(library.random().is_some());
+    }
+}
@@ -1,11 +1,23 @@
+pub mod config;
 pub mod file;
 pub mod handler;
+pub mod history;
+pub mod library;
+pub mod replays;
+pub mod stats;
+pub mod themes;
 
-// For this dummy application we only need two IO event
 #[derive(Debug, Clone)]
 pub enum IoEvent {
     Initialize,
-    Timer,
-    TimeUp,
-    FinishText,
+    /// Starts a test with the mode and text chosen in the app.
+    StartTest,
+    /// Starts a test on the text following the current one.
+    NextText,
+    /// Loads the stored sessions and shows them.
+    OpenHistory,
+    /// Writes the settings edited in the app to the config file.
+    SaveConfig,
+    /// Saves the test that just ended to the history and the key statistics.
+    SaveSession,
 }
@@ -0,0 +1,169 @@
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use eyre::{Result, WrapErr};
+use log::warn;
+
+use crate::app::replay::Replay;
+use crate::engine::mode::TestMode;
+use crate::io::history::data_dir;
+
+const REPLAYS_DIR: &str = "replays";
+
+/// Keeps each session as a JSON file, named after its time so that the
+/// names sort oldest first.
+#[derive(Debug, Clone)]
+pub struct ReplayStore {
+    dir: PathBuf,
+}
+
+impl ReplayStore {
+    pub fn new(dir: impl Into<PathBuf>) -> Self {
+        Self { dir: dir.into() }
+    }
+
+    pub fn dir(&self) -> &Path {
+        &self.dir
+    }
+
+    /// Writes the replay to a new file, and returns its path. Replays are
+    /// never removed, so that any score can be checked against its inputs.
+    pub fn save(&self, replay: &Replay) -> Result<PathBuf> {
+        fs::create_dir_all(&self.dir)
+            .wrap_err_with(|| format!("error creating directory {}", self.dir.display()))?;
+
+        let name = format!(
+            "{}-{}-{}.json",
+            replay.timestamp.format("%Y%m%d-%H%M%S%.3f"),
+            replay.passage_id,
+            replay.mode
+        );
+        let path = self.dir.join(name);
+        fs::write(&path, serde_json::to_string(replay)?)
+            .wrap_err_with(|| format!("error writing {}", path.display()))?;
+
+        Ok(path)
+    }
+
+    pub fn read(path: &Path) -> Result<Replay> {
+        let content = fs::read_to_string(path)
+            .wrap_err_with(|| format!("error reading {}", path.display()))?;
+        serde_json::from_str(&content).wrap_err_with(|| format!("error parsing {}", path.display()))
+    }
+
+    /// Every replay file, oldest first.
+    pub fn paths(&self) -> Result<Vec<PathBuf>> {
+        if !self.dir.is_dir() {
+            return Ok(vec![]);
+        }
+
+        let entries = fs::read_dir(&self.dir)
+            .wrap_err_with(|| format!("error reading {}", self.dir.display()))?;
+        let mut paths = entries
+            .filter_map(|entry| entry.ok().map(|entry| entry.path()))
+            .filter(|path| path.extension().is_some_and(|ext| ext == "json"))
+            .collect::<Vec<_>>();
+        paths.sort();
+
+        Ok(paths)
+    }
+
+    pub fn last(&self) -> Result<Option<PathBuf>> {
+        Ok(self.paths()?.pop())
+    }
+
+    /// Every replay of the passage in this mode, oldest first. Files that
+    /// cannot be read are skipped.
+    pub fn find(&self, passage_id: &str, mode: TestMode) -> Result<Vec<Replay>> {
+        Ok(self
+            .entries(passage_id, mode)?
+            .into_iter()
+            .map(|(_, replay)| replay)
+            .collect())
+    }
+
+    fn entries(&self, passage_id: &str, mode: TestMode) -> Result<Vec<(PathBuf, Replay)>> {
+        let suffix = format!("-{}-{}.json", passage_id, mode);
+        let entries = self
+            .paths()?
+            .into_iter()
+            .filter(|path| path.to_string_lossy().ends_with(&suffix))
+            .filter_map(|path| match Self::read(&path) {
+                Ok(replay) => Some((path, replay)),
+                Err(err) => {
+                    warn!("Skip replay {}: {:?}", path.display(), err);
+                    None
+                }
+            })
+            .filter(|(_, replay)| replay.passage_id == passage_id && replay.mode == mode)
+            .collect();
+
+        Ok(entries)
+    }
+}
+
+impl Default for ReplayStore {
+    fn default() -> Self {
+        Self::new(data_dir().join(REPLAYS_DIR))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::engine::Input;
+
+    fn replay(id: &str) -> Replay {
+        Replay::sample(id, 40.0, &[(Input::Char('a'), 0), (Input::Char('b'), 300)])
+    }
+
+    #[test]
+    fn should_save_and_find_the_last_replay() {
+        let dir = tempfile::tempdir().unwrap();
+        let store = ReplayStore::new(dir.path().join(REPLAYS_DIR));
+        assert_eq!(store.last().unwrap(), None);
+
+        let first = replay("1");
+        let mut second = replay("2");
+        second.timestamp = first.timestamp + chrono::Duration::seconds(1);
+        let second_path = store.save(&second).unwrap();
+        store.save(&first).unwrap();
+
+        assert_eq!(store.paths().unwrap().len(), 2);
+        assert_eq!(store.last().unwrap(), Some(second_path.clone()));
+        assert_eq!(ReplayStore::read(&second_path).unwrap(), second);
+        assert!(second_path.ends_with(format!(
+            "{}-2-passage.json",
+            second.timestamp.format("%Y%m%d-%H%M%S%.3f")
+        )));
+    }
+
+    #[test]
+    fn should_find_the_replays_of_a_passage() {
+        let dir = tempfile::tempdir().unwrap();
+        let store = ReplayStore::new(dir.path());
+        assert!(store.find("1", TestMode::Passage).unwrap().is_empty());
+
+        let first = replay("1");
+        let mut second = replay("1");
+        second.timestamp = first.timestamp + chrono::Duration::seconds(1);
+        let mut other = replay("x-1");
+        other.timestamp = first.timestamp + chrono::Duration::seconds(2);
+        for replay in [&second, &first, &other] {
+            store.save(replay).unwrap();
+        }
+        fs::write(dir.path().join("broken-1-passage.json"), "{").unwrap();
+
+        assert_eq!(store.find("1", TestMode::Passage).unwrap(), [first, second]);
+        assert!(store.find("1", TestMode::Words(10)).unwrap().is_empty());
+    }
+
+    #[test]
+    fn should_fail_on_malformed_file() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("broken.json");
+        fs::write(&path, "{").unwrap();
+
+        assert!(ReplayStore::read(&path).is_err());
+    }
+}
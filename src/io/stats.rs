@@ -0,0 +1,100 @@
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use eyre::{Result, WrapErr};
+
+use crate::engine::stats::TypingStats;
+use crate::io::history::data_dir;
+
+const STATS_FILE: &str = "stats.json";
+
+/// Keeps the per-key and per-bigram statistics of every session, added
+/// together.
+#[derive(Debug, Clone)]
+pub struct StatsStore {
+    path: PathBuf,
+}
+
+impl StatsStore {
+    pub fn new(path: impl Into<PathBuf>) -> Self {
+        Self { path: path.into() }
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    pub fn load(&self) -> Result<TypingStats> {
+        if !self.path.exists() {
+            return Ok(TypingStats::default());
+        }
+
+        let content = fs::read_to_string(&self.path)
+            .wrap_err_with(|| format!("error reading {}", self.path.display()))?;
+        serde_json::from_str(&content)
+            .wrap_err_with(|| format!("error parsing {}", self.path.display()))
+    }
+
+    /// Adds the statistics of a session to the stored ones.
+    pub fn add(&self, session: &TypingStats) -> Result<TypingStats> {
+        let mut stats = self.load()?;
+        stats.merge(session);
+
+        if let Some(parent) = self.path.parent() {
+            fs::create_dir_all(parent)
+                .wrap_err_with(|| format!("error creating directory {}", parent.display()))?;
+        }
+        let content = serde_json::to_string(&stats)?;
+        fs::write(&self.path, content)
+            .wrap_err_with(|| format!("error writing {}", self.path.display()))?;
+
+        Ok(stats)
+    }
+}
+
+impl Default for StatsStore {
+    fn default() -> Self {
+        Self::new(data_dir().join(STATS_FILE))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use super::*;
+    use crate::engine::score::Keystroke;
+
+    #[test]
+    fn should_add_sessions_together() {
+        let dir = tempfile::tempdir().unwrap();
+        let store = StatsStore::new(dir.path().join("nested").join(STATS_FILE));
+        assert_eq!(store.load().unwrap(), TypingStats::default());
+
+        let session = TypingStats::compute(
+            "a",
+            &[Keystroke::Typed {
+                position: 0,
+                expected: Some('a'),
+                typed: 'b',
+                at: Duration::ZERO,
+            }],
+        );
+        store.add(&session).unwrap();
+        store.add(&session).unwrap();
+
+        let stats = store.load().unwrap();
+        assert_eq!(stats.key('a').unwrap().count, 2);
+        assert_eq!(stats.key('a').unwrap().errors, 2);
+    }
+
+    #[test]
+    fn should_fail_on_malformed_file() {
+        let dir = tempfile::tempdir().unwrap();
+        let store = StatsStore::new(dir.path().join(STATS_FILE));
+        fs::write(store.path(), "not json").unwrap();
+
+        assert!(store.load().is_err());
+        assert!(store.add(&TypingStats::default()).is_err());
+    }
+}
@@ -0,0 +1,98 @@
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use eyre::{Result, WrapErr};
+use log::error;
+
+use crate::app::theme::Theme;
+use crate::io::config::config_dir;
+
+/// Custom themes, one TOML file per theme named after the file.
+#[derive(Debug, Clone)]
+pub struct ThemeStore {
+    dir: PathBuf,
+}
+
+impl ThemeStore {
+    pub fn new(dir: impl Into<PathBuf>) -> Self {
+        Self { dir: dir.into() }
+    }
+
+    pub fn dir(&self) -> &Path {
+        &self.dir
+    }
+
+    /// Every readable theme, sorted by name. Files that cannot be read are
+    /// skipped.
+    pub fn load(&self) -> Result<Vec<Theme>> {
+        if !self.dir.exists() {
+            return Ok(vec![]);
+        }
+
+        let entries = fs::read_dir(&self.dir)
+            .wrap_err_with(|| format!("error reading {}", self.dir.display()))?;
+        let mut themes = entries
+            .filter_map(|entry| entry.ok().map(|entry| entry.path()))
+            .filter(|path| path.extension().is_some_and(|ext| ext == "toml"))
+            .filter_map(|path| match read_theme(&path) {
+                Ok(theme) => Some(theme),
+                Err(err) => {
+                    error!("Skip theme {}: {:?}", path.display(), err);
+                    None
+                }
+            })
+            .collect::<Vec<_>>();
+        themes.sort_by(|a, b| a.name.cmp(&b.name));
+
+        Ok(themes)
+    }
+}
+
+impl Default for ThemeStore {
+    fn default() -> Self {
+        Self::new(config_dir().join("themes"))
+    }
+}
+
+fn read_theme(path: &Path) -> Result<Theme> {
+    let name = path
+        .file_stem()
+        .map(|stem| stem.to_string_lossy().into_owned())
+        .unwrap_or_default();
+    let content =
+        fs::read_to_string(path).wrap_err_with(|| format!("error reading {}", path.display()))?;
+    Theme::from_toml(&name, &content)
+}
+
+#[cfg(test)]
+mod tests {
+    use tui::style::Color;
+
+    use super::*;
+
+    #[test]
+    fn should_load_themes_from_directory() {
+        let dir = tempfile::tempdir().unwrap();
+        fs::write(dir.path().join("zen.toml"), "correct = \"blue\"").unwrap();
+        fs::write(dir.path().join("broken.toml"), "correct = \"mauve\"").unwrap();
+        fs::write(dir.path().join("notes.txt"), "not a theme").unwrap();
+        fs::write(dir.path().join("amber.toml"), "text = \"yellow\"").unwrap();
+
+        let themes = ThemeStore::new(dir.path()).load().unwrap();
+        let names = themes
+            .iter()
+            .map(|theme| theme.name.as_str())
+            .collect::<Vec<_>>();
+
+        assert_eq!(names, vec!["amber", "zen"]);
+        assert_eq!(themes[1].correct, Color::Blue);
+    }
+
+    #[test]
+    fn should_have_no_theme_without_directory() {
+        let dir = tempfile::tempdir().unwrap();
+        let store = ThemeStore::new(dir.path().join("missing"));
+
+        assert!(store.load().unwrap().is_empty());
+    }
+}
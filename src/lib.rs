@@ -1,73 +1,112 @@
 use std::io::stdout;
+use std::panic;
 use std::sync::Arc;
-use std::time::Duration;
 
 use app::{App, AppReturn};
-use eyre::Result;
-use inputs::events::Events;
+use eyre::{eyre, Result};
+use inputs::events::{EventSource, Events};
 use inputs::InputEvent;
 use io::IoEvent;
-use tui::backend::CrosstermBackend;
+use tui::backend::{Backend, CrosstermBackend};
+use tui::layout::Rect;
 use tui::Terminal;
 
 use crate::app::ui;
 
 pub mod app;
+pub mod cli;
+pub mod engine;
 pub mod inputs;
 pub mod io;
 
-pub async fn start_app(app: &Arc<tokio::sync::Mutex<App>>) -> Result<()> {
-    let stdout = stdout();
-    crossterm::terminal::enable_raw_mode()?;
-    let backend = CrosstermBackend::new(stdout);
-    let mut terminal = Terminal::new(backend)?;
+/// Puts the terminal in raw mode and restores it when dropped, or when any
+/// thread panics, so the shell is always left usable.
+struct TerminalGuard;
+
+impl TerminalGuard {
+    fn new() -> Result<Self> {
+        let default_hook = panic::take_hook();
+        panic::set_hook(Box::new(move |info| {
+            restore_terminal();
+            default_hook(info);
+        }));
+
+        crossterm::terminal::enable_raw_mode()?;
+        Ok(Self)
+    }
+}
+
+impl Drop for TerminalGuard {
+    fn drop(&mut self) {
+        restore_terminal();
+    }
+}
+
+fn restore_terminal() {
+    if let Err(err) = crossterm::terminal::disable_raw_mode() {
+        eprintln!("Cannot disable raw mode: {}", err);
+    }
+    if let Err(err) = crossterm::execute!(stdout(), crossterm::cursor::Show) {
+        eprintln!("Cannot show the cursor: {}", err);
+    }
+}
+
+/// Runs the app in the terminal, reading keys from it, until the user quits.
+pub async fn start_terminal_app(app: &Arc<tokio::sync::Mutex<App>>) -> Result<()> {
+    let _guard = TerminalGuard::new()?;
+    let mut terminal = Terminal::new(CrosstermBackend::new(stdout()))?;
     terminal.clear()?;
     terminal.hide_cursor()?;
 
-    let tick_rate = Duration::from_millis(100);
-    let mut events = Events::new(tick_rate);
+    let tick_rate = app.lock().await.settings().tick_rate();
+    start_app(app, &mut terminal, Events::new(tick_rate)).await?;
 
-    {
-        let mut app = app.lock().await;
-        app.dispatch(IoEvent::Initialize).await;
-    }
+    terminal.clear()?;
+    terminal.show_cursor()?;
 
-    let app_clock = Arc::clone(&app);
-    tokio::spawn(async move {
-        tokio::time::sleep(Duration::from_secs(1)).await;
-        loop {
-            tokio::time::sleep(Duration::from_secs(1)).await;
-            app_clock.lock().await.dispatch(IoEvent::Timer).await;
-        }
-    });
+    Ok(())
+}
+
+/// Draws the app on the given terminal and feeds it the events of the given
+/// source, until the app exits. Fails when the source ends first.
+pub async fn start_app<B, E>(
+    app: &Arc<tokio::sync::Mutex<App>>,
+    terminal: &mut Terminal<B>,
+    mut events: E,
+) -> Result<()>
+where
+    B: Backend,
+    E: EventSource,
+{
+    app.lock().await.dispatch(IoEvent::Initialize).await;
 
     loop {
-        let mut app = app.lock().await;
+        {
+            let app = app.lock().await;
+            terminal.draw(|rect| ui::draw(rect, &app))?;
+        }
 
-        terminal.draw(|rect| ui::draw(rect, &app))?;
+        // The app is not locked while waiting, so IO events are handled in
+        // the meantime.
+        let Some(event) = events.next().await else {
+            return Err(eyre!("No more inputs to read, the terminal may be gone"));
+        };
+        let mut app = app.lock().await;
 
-        let result = match events.next().await {
+        let result = match event {
             InputEvent::Input(key) => app.do_action(key).await,
             InputEvent::Tick => app.tick().await,
+            InputEvent::Resize(width, height) => {
+                terminal.resize(Rect::new(0, 0, width, height))?;
+                AppReturn::Continue
+            }
         };
 
-        if app.state().is_time_over() {
-            app.dispatch(IoEvent::TimeUp).await;
-        }
-
-        if app.state().is_finished() {
-            app.dispatch(IoEvent::FinishText).await;
-        }
-
         if result == AppReturn::Exit {
             events.close();
             break;
         }
     }
 
-    terminal.clear()?;
-    terminal.show_cursor()?;
-    crossterm::terminal::disable_raw_mode()?;
-
     Ok(())
 }
@@ -1,31 +1,72 @@
 use std::sync::Arc;
 
+use clap::Parser;
 use eyre::Result;
 use jackdull::app::App;
+use jackdull::cli::{self, Cli, Command};
+use jackdull::io::config::ConfigStore;
 use jackdull::io::handler::IoAsyncHandler;
+use jackdull::io::history::HistoryStore;
+use jackdull::io::library::TextLibrary;
+use jackdull::io::replays::ReplayStore;
+use jackdull::io::stats::StatsStore;
+use jackdull::io::themes::ThemeStore;
 use jackdull::io::IoEvent;
-use jackdull::start_app;
-use log::LevelFilter;
+use jackdull::start_terminal_app;
+use log::error;
 
 #[tokio::main]
 async fn main() -> Result<()> {
+    let cli = Cli::parse();
+
+    let config_store = cli
+        .config
+        .clone()
+        .map_or_else(ConfigStore::default, ConfigStore::new);
+    let config = config_store.load()?;
+    let mut overrides = cli.overrides();
+    let mut library = TextLibrary::new(config.merged(&overrides).texts_dir);
+
+    let replay = match &cli.command {
+        Some(Command::Replay { file, speed }) => Some((cli::load_replay(file.as_deref())?, *speed)),
+        Some(Command::Print(command)) => return cli::run(command, &mut library),
+        None => None,
+    };
+    if let Some(text) = &cli.text {
+        overrides.text = Some(cli::resolve_text(&mut library, text)?);
+    }
+
     let (sync_io_tx, mut sync_io_rx) = tokio::sync::mpsc::channel::<IoEvent>(100);
 
-    let app = Arc::new(tokio::sync::Mutex::new(App::new(sync_io_tx.clone())));
+    let mut app = App::with_config(sync_io_tx.clone(), config, &overrides);
+    if let Some((replay, speed)) = &replay {
+        app.play_replay(replay, *speed);
+    }
+    let app = Arc::new(tokio::sync::Mutex::new(app));
     let app_ui = Arc::clone(&app);
 
-    tui_logger::init_logger(LevelFilter::Debug).unwrap();
-    tui_logger::set_default_level(log::LevelFilter::Debug);
+    tui_logger::init_logger(cli.log_level.filter()).unwrap();
+    tui_logger::set_default_level(cli.log_level.filter());
 
     tokio::spawn(async move {
-        let mut handler = IoAsyncHandler::new(app);
+        let mut handler = IoAsyncHandler::new(
+            app,
+            library,
+            HistoryStore::default(),
+            StatsStore::default(),
+            ThemeStore::default(),
+            config_store,
+            ReplayStore::default(),
+        );
 
         while let Some(io_event) = sync_io_rx.recv().await {
-            handler.handle_io_event(io_event).await;
+            if let Err(err) = handler.handle_io_event(io_event).await {
+                error!("Cannot handle IO event: {}", err);
+            }
         }
     });
 
-    start_app(&app_ui).await?;
+    start_terminal_app(&app_ui).await?;
 
     Ok(())
 }
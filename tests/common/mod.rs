@@ -0,0 +1,226 @@
+//! Runs the app on a test terminal, with a script of inputs and a clock that
+//! only moves when the script says so.
+
+// Each test crate uses its own part of the harness.
+#![allow(dead_code)]
+
+use std::collections::VecDeque;
+use std::fs;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+use jackdull::app::clock::Clock;
+use jackdull::app::config::{Config, Overrides};
+use jackdull::app::replay::Replay;
+use jackdull::app::ui;
+use jackdull::app::App;
+use jackdull::inputs::events::EventSource;
+use jackdull::inputs::key::Key;
+use jackdull::inputs::InputEvent;
+use jackdull::io::config::ConfigStore;
+use jackdull::io::handler::IoAsyncHandler;
+use jackdull::io::history::HistoryStore;
+use jackdull::io::library::TextLibrary;
+use jackdull::io::replays::ReplayStore;
+use jackdull::io::stats::StatsStore;
+use jackdull::io::themes::ThemeStore;
+use jackdull::io::IoEvent;
+use jackdull::start_app;
+use tempfile::TempDir;
+use tui::backend::TestBackend;
+use tui::Terminal;
+
+pub const WIDTH: u16 = 100;
+pub const HEIGHT: u16 = 40;
+
+/// A clock that stands still until it is moved forward.
+pub struct FakeClock {
+    now: Mutex<Instant>,
+}
+
+impl FakeClock {
+    pub fn new() -> Self {
+        Self {
+            now: Mutex::new(Instant::now()),
+        }
+    }
+
+    pub fn advance(&self, duration: Duration) {
+        *self.now.lock().unwrap() += duration;
+    }
+}
+
+impl Clock for FakeClock {
+    fn now(&self) -> Instant {
+        *self.now.lock().unwrap()
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+pub enum Step {
+    Key(Key),
+    /// Moves the clock forward, then ticks.
+    Wait(Duration),
+}
+
+/// Types each character of `text`, `interval` apart.
+pub fn typing(text: &str, interval: Duration) -> Vec<Step> {
+    text.chars()
+        .flat_map(|c| {
+            let key = if c == '\n' { Key::Enter } else { Key::Char(c) };
+            [Step::Key(key), Step::Wait(interval)]
+        })
+        .collect()
+}
+
+pub fn millis(millis: u64) -> Duration {
+    Duration::from_millis(millis)
+}
+
+/// Plays the steps one by one, once the IO events sent by the previous one
+/// are handled, then quits.
+struct ScriptedEvents {
+    steps: VecDeque<Step>,
+    clock: Arc<FakeClock>,
+    handler: IoAsyncHandler,
+    io_rx: tokio::sync::mpsc::Receiver<IoEvent>,
+}
+
+impl EventSource for ScriptedEvents {
+    async fn next(&mut self) -> Option<InputEvent> {
+        while let Ok(io_event) = self.io_rx.try_recv() {
+            // Errors end up on the error screen, where the tests look for them.
+            let _ = self.handler.handle_io_event(io_event).await;
+        }
+
+        let event = match self.steps.pop_front() {
+            Some(Step::Key(key)) => InputEvent::Input(key),
+            Some(Step::Wait(duration)) => {
+                self.clock.advance(duration);
+                InputEvent::Tick
+            }
+            None => InputEvent::Input(Key::Ctrl('c')),
+        };
+        Some(event)
+    }
+
+    fn close(&mut self) {}
+}
+
+/// What is left once the script is played: the app, its last frame, and the
+/// directory holding the history and the statistics.
+pub struct Run {
+    pub app: App,
+    pub terminal: Terminal<TestBackend>,
+    pub dir: TempDir,
+}
+
+impl Run {
+    /// The last frame, one line per row.
+    pub fn screen(&self) -> String {
+        let buffer = self.terminal.backend().buffer();
+        buffer
+            .content()
+            .chunks(buffer.area.width as usize)
+            .map(|row| {
+                row.iter()
+                    .map(|cell| cell.symbol.as_str())
+                    .collect::<String>()
+            })
+            .collect::<Vec<_>>()
+            .join("\n")
+    }
+
+    /// The replays recorded during the run, oldest first.
+    pub fn replays(&self) -> Vec<Replay> {
+        ReplayStore::new(self.dir.path().join("replays"))
+            .paths()
+            .unwrap()
+            .iter()
+            .map(|path| ReplayStore::read(path).unwrap())
+            .collect()
+    }
+
+    pub fn history(&self) -> Vec<String> {
+        fs::read_to_string(self.dir.path().join("history.jsonl"))
+            .unwrap_or_default()
+            .lines()
+            .map(str::to_owned)
+            .collect()
+    }
+}
+
+/// Id of the text given to `run`.
+pub const DRILL: &str = "drill";
+
+/// Plays `steps` on the app started with `overrides`, with `text` added to
+/// the bundled texts as `DRILL`.
+pub async fn run(text: &str, overrides: Overrides, steps: Vec<Step>) -> Run {
+    start(tempfile::tempdir().unwrap(), text, overrides, None, steps).await
+}
+
+/// Like `run`, in the directory of `previous`, so that its sessions are
+/// already stored.
+pub async fn run_after(previous: Run, text: &str, overrides: Overrides, steps: Vec<Step>) -> Run {
+    start(previous.dir, text, overrides, None, steps).await
+}
+
+/// Plays `steps` while `replay` is played back, `speed` times faster.
+pub async fn run_replay(replay: &Replay, speed: f64, steps: Vec<Step>) -> Run {
+    start(
+        tempfile::tempdir().unwrap(),
+        &replay.text,
+        Overrides::default(),
+        Some((replay, speed)),
+        steps,
+    )
+    .await
+}
+
+async fn start(
+    dir: TempDir,
+    text: &str,
+    overrides: Overrides,
+    replay: Option<(&Replay, f64)>,
+    steps: Vec<Step>,
+) -> Run {
+    let text_path = dir.path().join(format!("{}.txt", DRILL));
+    fs::write(&text_path, text).unwrap();
+    let mut library = TextLibrary::new(dir.path().join("texts"));
+    library.add_file(&text_path).unwrap();
+
+    let clock = Arc::new(FakeClock::new());
+    let (io_tx, io_rx) = tokio::sync::mpsc::channel(100);
+    let mut app = App::with_config(io_tx, Config::default(), &overrides);
+    app.set_clock(clock.clone());
+    if let Some((replay, speed)) = replay {
+        app.play_replay(replay, speed);
+    }
+    let app = Arc::new(tokio::sync::Mutex::new(app));
+
+    let handler = IoAsyncHandler::new(
+        Arc::clone(&app),
+        library,
+        HistoryStore::new(dir.path().join("history.jsonl")),
+        StatsStore::new(dir.path().join("stats.json")),
+        ThemeStore::new(dir.path().join("themes")),
+        ConfigStore::new(dir.path().join("config.toml")),
+        ReplayStore::new(dir.path().join("replays")),
+    );
+    let events = ScriptedEvents {
+        steps: steps.into(),
+        clock,
+        handler,
+        io_rx,
+    };
+
+    let mut terminal = Terminal::new(TestBackend::new(WIDTH, HEIGHT)).unwrap();
+    start_app(&app, &mut terminal, events).await.unwrap();
+
+    let app = Arc::try_unwrap(app)
+        .unwrap_or_else(|_| panic!("the app is still shared"))
+        .into_inner();
+    terminal.draw(|rect| ui::draw(rect, &app)).unwrap();
+
+    Run { app, terminal, dir }
+}
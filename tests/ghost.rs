@@ -0,0 +1,101 @@
+mod common;
+
+use common::{millis, run, run_after, typing, DRILL};
+use jackdull::app::config::Overrides;
+use jackdull::app::ghost::GhostRun;
+use jackdull::engine::mode::TestMode;
+
+fn racing(ghost: GhostRun) -> Overrides {
+    Overrides {
+        mode: Some(TestMode::Passage),
+        text: Some(DRILL.to_owned()),
+        ghost: Some(ghost),
+        ..Overrides::default()
+    }
+}
+
+#[tokio::test(start_paused = true)]
+async fn should_race_the_best_run() {
+    let slow = run(
+        "one two",
+        racing(GhostRun::Best),
+        typing("one two", millis(200)),
+    )
+    .await;
+    assert!(slow.app.state().ghost().is_none());
+
+    // 400ms in, the ghost has typed its first two characters.
+    let run = run_after(
+        slow,
+        "one two",
+        racing(GhostRun::Best),
+        typing("one ", millis(100)),
+    )
+    .await;
+
+    let ghost = run.app.state().ghost().unwrap();
+    assert_eq!(ghost.run, GhostRun::Best);
+    assert!(
+        run.screen().contains("+2 chars ahead of best run"),
+        "{}",
+        run.screen()
+    );
+}
+
+#[tokio::test(start_paused = true)]
+async fn should_race_the_last_run_of_the_same_mode() {
+    let first = run(
+        "one two",
+        racing(GhostRun::Last),
+        typing("one two", millis(50)),
+    )
+    .await;
+    let second = run_after(
+        first,
+        "one two",
+        racing(GhostRun::Last),
+        typing("one two", millis(300)),
+    )
+    .await;
+
+    // 200ms in, the first run is 4 characters in and the second one only 1.
+    let run = run_after(
+        second,
+        "one two",
+        racing(GhostRun::Last),
+        typing("on", millis(100)),
+    )
+    .await;
+    assert!(
+        run.screen().contains("+1 chars ahead of last run"),
+        "{}",
+        run.screen()
+    );
+
+    let words = Overrides {
+        mode: Some(TestMode::Words(2)),
+        ..racing(GhostRun::Last)
+    };
+    let run = run_after(run, "one two", words, typing("on", millis(300))).await;
+    assert!(run.app.state().ghost().is_none());
+}
+
+#[tokio::test(start_paused = true)]
+async fn should_race_on_the_text_of_the_ghost() {
+    let words = Overrides {
+        mode: Some(TestMode::Words(4)),
+        ..racing(GhostRun::Last)
+    };
+    let first = run(
+        "one two three four",
+        words.clone(),
+        typing("one two three four", millis(50)),
+    )
+    .await;
+
+    // The passage is now shorter than the goal, and would be padded with
+    // random texts without the ghost.
+    let run = run_after(first, "one two", words, typing("on", millis(100))).await;
+    assert!(run.app.state().ghost().is_some());
+    assert_eq!(run.app.state().to_type(), Some("one two three four"));
+}
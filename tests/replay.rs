@@ -0,0 +1,69 @@
+mod common;
+
+use common::{millis, run, run_replay, typing, Step, DRILL};
+use jackdull::app::config::Overrides;
+use jackdull::engine::mode::TestMode;
+use jackdull::engine::score::EndReason;
+use jackdull::inputs::key::Key;
+
+async fn recorded() -> jackdull::app::replay::Replay {
+    let overrides = Overrides {
+        mode: Some(TestMode::Passage),
+        text: Some(DRILL.to_owned()),
+        ..Overrides::default()
+    };
+    let mut steps = typing("one tx", millis(200));
+    steps.push(Step::Key(Key::Backspace));
+    steps.extend(typing("wo", millis(200)));
+
+    let mut replays = run("one two", overrides, steps).await.replays();
+    assert_eq!(replays.len(), 1);
+    replays.remove(0)
+}
+
+#[tokio::test(start_paused = true)]
+async fn should_record_every_keystroke() {
+    let replay = recorded().await;
+
+    assert_eq!(replay.passage_id, DRILL);
+    assert_eq!(replay.mode, TestMode::Passage);
+    assert_eq!(replay.text, "one two");
+    let offsets = replay
+        .keystrokes
+        .iter()
+        .map(|keystroke| keystroke.at().as_millis())
+        .collect::<Vec<_>>();
+    assert_eq!(offsets, vec![0, 200, 400, 600, 800, 1000, 1200, 1200, 1400]);
+    assert_eq!(replay.result().unwrap().score.net_wpm, replay.wpm);
+}
+
+#[tokio::test(start_paused = true)]
+async fn should_type_the_replay_at_its_speed() {
+    let replay = recorded().await;
+
+    // The first tick starts the playback, the next ones are 600ms into it.
+    let steps = vec![
+        Step::Wait(millis(100)),
+        Step::Wait(millis(150)),
+        Step::Wait(millis(150)),
+    ];
+    let run = run_replay(&replay, 2.0, steps).await;
+
+    assert_eq!(run.app.state().typed_text(), Some("one ".to_owned()));
+    assert!(run.screen().contains("Replay x2"), "{}", run.screen());
+}
+
+#[tokio::test(start_paused = true)]
+async fn should_end_as_recorded_without_saving() {
+    let replay = recorded().await;
+
+    let run = run_replay(&replay, 4.0, vec![Step::Wait(millis(100)); 5]).await;
+
+    let result = run.app.state().result().unwrap();
+    assert_eq!(result.reason, EndReason::Finished);
+    assert_eq!(result.score.elapsed, millis(1400));
+    assert_eq!(result.score.net_wpm, replay.wpm);
+    assert!(run.screen().contains("Finished!"));
+    assert!(run.history().is_empty());
+    assert!(run.replays().is_empty());
+}
@@ -0,0 +1,144 @@
+mod common;
+
+use std::sync::Arc;
+use std::time::Duration;
+
+use common::{millis, run, typing, Step, DRILL, HEIGHT, WIDTH};
+use jackdull::app::config::Overrides;
+use jackdull::app::App;
+use jackdull::engine::mode::TestMode;
+use jackdull::engine::score::EndReason;
+use jackdull::inputs::events::EventSource;
+use jackdull::inputs::key::Key;
+use jackdull::inputs::InputEvent;
+use jackdull::start_app;
+use tui::backend::TestBackend;
+use tui::Terminal;
+
+/// Skips the menu, straight to a test on the drill.
+fn mode(mode: TestMode) -> Overrides {
+    Overrides {
+        mode: Some(mode),
+        text: Some(DRILL.to_owned()),
+        ..Overrides::default()
+    }
+}
+
+#[tokio::test(start_paused = true)]
+async fn should_finish_a_passage_and_save_it() {
+    let run = run(
+        "one two",
+        mode(TestMode::Passage),
+        typing("one two", millis(200)),
+    )
+    .await;
+
+    let result = run.app.state().result().unwrap();
+    assert_eq!(result.reason, EndReason::Finished);
+    // The clock starts with the first key, the seventh comes 1.2s later.
+    assert_eq!(result.score.elapsed, millis(1200));
+    assert_eq!(result.score.net_wpm.round(), 70.0);
+    assert_eq!(result.score.accuracy.percentage, 100.0);
+
+    let screen = run.screen();
+    assert!(screen.contains("Finished!"), "{}", screen);
+    assert!(
+        screen.contains("70 WPM (raw 70) - 350 CPM in 1.200s"),
+        "{}",
+        screen
+    );
+    assert_eq!(run.history().len(), 1);
+}
+
+#[tokio::test(start_paused = true)]
+async fn should_end_when_time_is_up() {
+    let mut steps = typing("onx", millis(500));
+    steps.push(Step::Wait(Duration::from_secs(5)));
+    steps.extend(typing("two", millis(100)));
+
+    let run = run(
+        "one two",
+        mode(TestMode::Timed(Duration::from_secs(2))),
+        steps,
+    )
+    .await;
+
+    let result = run.app.state().result().unwrap();
+    assert_eq!(result.reason, EndReason::TimeUp);
+    assert_eq!(result.score.elapsed, Duration::from_secs(2));
+    assert_eq!(result.score.typed_chars, 3);
+    assert_eq!(result.score.accuracy.uncorrected_errors, 1);
+    assert!(run.screen().contains("Time is up!"));
+    assert!(run.history()[0].contains("\"reason\":\"time_up\""));
+}
+
+#[tokio::test(start_paused = true)]
+async fn should_show_the_typed_text_and_live_stats() {
+    let mut steps = typing("one tx", millis(250));
+    steps.push(Step::Key(Key::Backspace));
+
+    let run = run("one two three", mode(TestMode::Passage), steps).await;
+
+    assert_eq!(run.app.state().typed_text(), Some("one t".to_owned()));
+    assert!(run.app.state().result().is_none());
+    let screen = run.screen();
+    assert!(screen.contains("one two three"), "{}", screen);
+    assert!(screen.contains("1/3 words (38%)"), "{}", screen);
+    assert!(screen.contains("1 errors"), "{}", screen);
+    assert!(run.history().is_empty());
+}
+
+#[tokio::test(start_paused = true)]
+async fn should_start_from_the_menu_and_retry() {
+    // Passage mode, then the drill after the bundled text, then start.
+    let mut steps = [
+        Key::Right,
+        Key::Right,
+        Key::Down,
+        Key::Down,
+        Key::Right,
+        Key::Right,
+        Key::Down,
+        Key::Down,
+    ]
+    .map(Step::Key)
+    .to_vec();
+    steps.push(Step::Key(Key::Enter));
+    steps.extend(typing("a b", millis(100)));
+    steps.push(Step::Key(Key::Ctrl('r')));
+    steps.extend(typing("a", millis(100)));
+
+    let run = run("a b", Overrides::default(), steps).await;
+
+    assert_eq!(run.app.mode(), TestMode::Passage);
+    assert_eq!(run.app.state().to_type(), Some("a b"));
+    assert_eq!(run.app.state().typed_text(), Some("a".to_owned()));
+    assert_eq!(run.history().len(), 1);
+    assert!(run.history()[0].contains("\"passage_id\":\"drill\",\"from\":\"drill.txt\""));
+}
+
+/// A few ticks, then nothing, like a terminal that went away.
+struct LostInput {
+    ticks: usize,
+}
+
+impl EventSource for LostInput {
+    async fn next(&mut self) -> Option<InputEvent> {
+        self.ticks = self.ticks.checked_sub(1)?;
+        Some(InputEvent::Tick)
+    }
+
+    fn close(&mut self) {}
+}
+
+#[tokio::test(start_paused = true)]
+async fn should_stop_when_the_inputs_end() {
+    let (io_tx, _io_rx) = tokio::sync::mpsc::channel(100);
+    let app = Arc::new(tokio::sync::Mutex::new(App::new(io_tx, None)));
+    let mut terminal = Terminal::new(TestBackend::new(WIDTH, HEIGHT)).unwrap();
+
+    let result = start_app(&app, &mut terminal, LostInput { ticks: 3 }).await;
+
+    let error = result.unwrap_err().to_string();
+    assert!(error.contains("No more inputs"), "{}", error);
+}